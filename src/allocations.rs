@@ -0,0 +1,119 @@
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    cell::Cell,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+/// A global allocator that counts the allocations made on each thread, so the runner can report
+/// what a part allocates with [`measure`]. It forwards everything to the system allocator.
+///
+/// It is installed in the binary with the `count-allocations` feature:
+///
+/// ```text
+/// cargo run --release --features count-allocations all
+/// ```
+pub struct CountingAllocator;
+
+/// Set on the first allocation through [`CountingAllocator`], so [`measure`] knows whether there
+/// is anything to measure.
+static INSTALLED: AtomicBool = AtomicBool::new(false);
+
+#[derive(Clone, Copy)]
+struct Counters {
+    allocations: u64,
+    bytes: u64,
+    /// Bytes allocated minus bytes freed on this thread. Can go negative when this thread frees
+    /// memory that another thread allocated.
+    current: i64,
+    peak: i64,
+}
+
+thread_local! {
+    // Const-initialized and without a destructor, so it can be used from within the allocator
+    static COUNTERS: Cell<Counters> = const {
+        Cell::new(Counters {
+            allocations: 0,
+            bytes: 0,
+            current: 0,
+            peak: 0,
+        })
+    };
+}
+
+fn record(allocated: usize, freed: usize) {
+    let _ = COUNTERS.try_with(|counters| {
+        let mut updated = counters.get();
+        if allocated > 0 {
+            updated.allocations += 1;
+            updated.bytes += allocated as u64;
+        }
+        updated.current += allocated as i64 - freed as i64;
+        updated.peak = updated.peak.max(updated.current);
+        counters.set(updated);
+    });
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() {
+            INSTALLED.store(true, Ordering::Relaxed);
+            record(layout.size(), 0);
+        }
+        ptr
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc_zeroed(layout);
+        if !ptr.is_null() {
+            INSTALLED.store(true, Ordering::Relaxed);
+            record(layout.size(), 0);
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        record(0, layout.size());
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let new_ptr = System.realloc(ptr, layout, new_size);
+        if !new_ptr.is_null() {
+            record(new_size, layout.size());
+        }
+        new_ptr
+    }
+}
+
+/// What a piece of code allocated.
+#[derive(Clone, Copy, Debug)]
+pub struct AllocationStats {
+    /// The number of allocations, counting reallocations as well.
+    pub allocations: u64,
+    /// The total number of bytes allocated, including memory that was freed again.
+    pub bytes: u64,
+    /// The largest amount of heap memory allocated at once, on top of what was allocated before.
+    pub peak: u64,
+}
+
+/// Run `f`, counting what it allocates on the current thread. Returns `None` for the stats if the
+/// [`CountingAllocator`] isn't installed.
+pub fn measure<T>(f: impl FnOnce() -> T) -> (T, Option<AllocationStats>) {
+    let before = COUNTERS.with(|counters| {
+        let mut before = counters.get();
+        before.peak = before.current;
+        counters.set(before);
+        before
+    });
+    let result = f();
+    let after = COUNTERS.with(Cell::get);
+
+    let stats = INSTALLED.load(Ordering::Relaxed).then(|| AllocationStats {
+        allocations: after.allocations - before.allocations,
+        bytes: after.bytes - before.bytes,
+        peak: (after.peak - before.current).max(0) as u64,
+    });
+
+    (result, stats)
+}
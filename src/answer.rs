@@ -0,0 +1,138 @@
+use std::fmt;
+
+/// The answer to one part of a day.
+///
+/// Answers are nearly always numbers, so they are kept as numbers where possible, which lets them
+/// be compared by value and written out as numbers. Integers that don't fit in an `i64` are big
+/// integers, and anything that isn't a number is text. Converting from any of the integer types
+/// picks the right variant, so solutions can end with `Ok(solution.into())`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Answer {
+    Integer(i64),
+    BigInteger(i128),
+    Text(String),
+}
+
+impl Answer {
+    /// The answer as an integer, if it is one.
+    pub fn as_integer(&self) -> Option<i128> {
+        match self {
+            Self::Integer(n) => Some((*n).into()),
+            Self::BigInteger(n) => Some(*n),
+            Self::Text(_) => None,
+        }
+    }
+
+    /// Whether the answer is `expected`, as written in an answers file. Integers are compared by
+    /// value, so an expected `054` matches an answer of 54.
+    pub fn matches(&self, expected: &str) -> bool {
+        match (self, self.as_integer()) {
+            (_, Some(n)) => expected
+                .trim()
+                .parse::<i128>()
+                .is_ok_and(|expected| expected == n),
+            (Self::Text(text), None) => text == expected,
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Display for Answer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Integer(n) => n.fmt(f),
+            Self::BigInteger(n) => n.fmt(f),
+            Self::Text(text) => text.fmt(f),
+        }
+    }
+}
+
+macro_rules! from_integers {
+    ($($integer:ty),*) => {
+        $(impl From<$integer> for Answer {
+            fn from(n: $integer) -> Self {
+                match i64::try_from(n) {
+                    Ok(n) => Self::Integer(n),
+                    Err(_) => Self::BigInteger(n as i128),
+                }
+            }
+        })*
+    };
+}
+
+from_integers!(i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, usize);
+
+// The only integers that may not fit in an `i128`, which are kept as text rather than wrapped
+impl From<u128> for Answer {
+    fn from(n: u128) -> Self {
+        match i128::try_from(n) {
+            Ok(n) => n.into(),
+            Err(_) => Self::Text(n.to_string()),
+        }
+    }
+}
+
+impl From<String> for Answer {
+    fn from(text: String) -> Self {
+        Self::Text(text)
+    }
+}
+
+impl From<&str> for Answer {
+    fn from(text: &str) -> Self {
+        Self::Text(text.to_owned())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn integers_that_fit_in_an_i64() {
+        assert_eq!(Answer::from(42u8), Answer::Integer(42));
+        assert_eq!(Answer::from(-7i32), Answer::Integer(-7));
+        assert_eq!(Answer::from(i64::MAX as u64), Answer::Integer(i64::MAX));
+        assert_eq!(Answer::from(5u128), Answer::Integer(5));
+    }
+
+    #[test]
+    fn big_integers() {
+        let above_i64 = i64::MAX as u64 + 1;
+        assert_eq!(
+            Answer::from(above_i64),
+            Answer::BigInteger(i128::from(above_i64))
+        );
+        assert_eq!(Answer::from(u64::MAX).to_string(), u64::MAX.to_string());
+        assert_eq!(Answer::from(i128::MIN), Answer::BigInteger(i128::MIN));
+        assert_eq!(
+            Answer::from(i128::MAX as u128),
+            Answer::BigInteger(i128::MAX)
+        );
+    }
+
+    #[test]
+    fn u128_above_i128_is_text() {
+        let above_i128 = i128::MAX as u128 + 1;
+        assert_eq!(
+            Answer::from(above_i128),
+            Answer::Text(above_i128.to_string())
+        );
+        assert_eq!(Answer::from(u128::MAX).as_integer(), None);
+    }
+
+    #[test]
+    fn matches_integers_by_value() {
+        assert!(Answer::from(54).matches("054"));
+        assert!(Answer::from(54).matches(" 54 "));
+        assert!(!Answer::from(54).matches("55"));
+        assert!(!Answer::from(54).matches("fifty-four"));
+        assert!(Answer::from(u64::MAX).matches("18446744073709551615"));
+    }
+
+    #[test]
+    fn matches_text_exactly() {
+        assert!(Answer::from("QQQJA").matches("QQQJA"));
+        assert!(!Answer::from("QQQJA").matches("qqqja"));
+    }
+}
@@ -0,0 +1,266 @@
+use std::{
+    collections::{hash_map::RandomState, BTreeMap},
+    fmt::{self, Display, Write},
+    fs,
+    hash::{BuildHasher, Hasher},
+    path::Path,
+    time::SystemTime,
+};
+
+use anyhow::{bail, Context, Result};
+use sha2::{Digest, Sha256};
+
+use crate::Answer;
+
+const HASH_PREFIX: &str = "sha256:";
+
+/// Expected answers per day and part, read from a small TOML file such as:
+///
+/// ```toml
+/// salt = "5f0c3a9e1b7d2468"
+///
+/// [day1]
+/// part1 = "54338"
+/// part2 = "sha256:9c1185a5c5e9fc54612808977ee8f548b2258d31..."
+/// ```
+///
+/// Answers can either be stored as plain text, or as a salted hash so the file can be published
+/// without giving the answers away. Hashes cover the salt, the day and the part as well as the
+/// answer, so equal answers don't produce equal hashes.
+#[derive(Default)]
+pub struct Answers {
+    salt: Option<String>,
+    entries: BTreeMap<(usize, u8), Expected>,
+}
+
+enum Expected {
+    Plain(String),
+    Hashed(String),
+}
+
+impl Answers {
+    pub fn load(path: &Path) -> Result<Self> {
+        let contents = fs::read_to_string(path)
+            .with_context(|| format!("Unable to read answers file {}", path.display()))?;
+
+        Self::parse(&contents)
+            .with_context(|| format!("Unable to parse answers file {}", path.display()))
+    }
+
+    /// Like [`Answers::load`], but starts out empty if there is no file at `path` yet.
+    pub fn load_or_default(path: &Path) -> Result<Self> {
+        if path.exists() {
+            Self::load(path)
+        } else {
+            Ok(Self::default())
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        fs::write(path, self.to_string())
+            .with_context(|| format!("Unable to write answers file {}", path.display()))
+    }
+
+    pub fn parse(s: &str) -> Result<Self> {
+        Self::parse_with_default_day(s, None)
+    }
+
+    /// Parse answers where entries before the first `[day#]` section belong to `day`, so a file
+    /// with answers for a single day doesn't need a section at all.
+    pub fn parse_for_day(s: &str, day: usize) -> Result<Self> {
+        Self::parse_with_default_day(s, Some(day))
+    }
+
+    fn parse_with_default_day(s: &str, default_day: Option<usize>) -> Result<Self> {
+        let mut answers = Self::default();
+        let mut day = default_day;
+        let mut in_section = false;
+        for (index, line) in s.lines().enumerate() {
+            let line = line.split_once('#').map_or(line, |(line, _)| line).trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            let result = if let Some(section) = line.strip_prefix('[') {
+                parse_section(section).map(|section_day| {
+                    day = Some(section_day);
+                    in_section = true;
+                })
+            } else {
+                parse_entry(line).and_then(|(key, value)| {
+                    match (key, day) {
+                        ("salt", _) if !in_section => answers.salt = Some(value),
+                        ("salt", _) => bail!("The salt must come before any [day#] section"),
+                        (key, Some(day)) => {
+                            let part = match key {
+                                "part1" => 1,
+                                "part2" => 2,
+                                _ => bail!("Unknown key \"{key}\", expected part1 or part2"),
+                            };
+                            let expected = match value.strip_prefix(HASH_PREFIX) {
+                                Some(hash) => Expected::Hashed(hash.to_owned()),
+                                None => Expected::Plain(value),
+                            };
+                            answers.entries.insert((day, part), expected);
+                        }
+                        (_, None) => bail!("Answer outside of a [day#] section"),
+                    }
+                    Ok(())
+                })
+            };
+            result.with_context(|| format!("Error on line {}", index + 1))?;
+        }
+
+        Ok(answers)
+    }
+
+    pub fn contains(&self, day: usize, part: u8) -> bool {
+        self.entries.contains_key(&(day, part))
+    }
+
+    pub fn matches(&self, day: usize, part: u8, answer: &Answer) -> bool {
+        match self.entries.get(&(day, part)) {
+            Some(Expected::Plain(expected)) => answer.matches(expected),
+            Some(Expected::Hashed(expected)) => {
+                *expected == self.hash(day, part, &answer.to_string())
+            }
+            None => false,
+        }
+    }
+
+    /// The expected answer for display, which is only a placeholder if it is hashed.
+    pub fn describe(&self, day: usize, part: u8) -> Option<&str> {
+        match self.entries.get(&(day, part))? {
+            Expected::Plain(expected) => Some(expected),
+            Expected::Hashed(_) => Some("<hashed>"),
+        }
+    }
+
+    /// Store the hash of `answer` as the expected answer, generating a salt first if needed.
+    pub fn record(&mut self, day: usize, part: u8, answer: &Answer) {
+        if self.salt.is_none() {
+            self.salt = Some(generate_salt());
+        }
+        let hash = self.hash(day, part, &answer.to_string());
+        self.entries.insert((day, part), Expected::Hashed(hash));
+    }
+
+    fn hash(&self, day: usize, part: u8, answer: &str) -> String {
+        let salt = self.salt.as_deref().unwrap_or_default();
+        let digest = Sha256::digest(format!("{salt}:{day}:{part}:{answer}"));
+
+        digest.iter().fold(String::new(), |mut hex, byte| {
+            let _ = write!(hex, "{byte:02x}");
+            hex
+        })
+    }
+}
+
+impl Display for Answers {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(salt) = &self.salt {
+            writeln!(f, "salt = \"{salt}\"")?;
+        }
+
+        let mut current_day = None;
+        for (&(day, part), expected) in &self.entries {
+            if current_day != Some(day) {
+                if current_day.is_some() || self.salt.is_some() {
+                    writeln!(f)?;
+                }
+                writeln!(f, "[day{day}]")?;
+                current_day = Some(day);
+            }
+            match expected {
+                Expected::Plain(answer) => writeln!(f, "part{part} = \"{answer}\"")?,
+                Expected::Hashed(hash) => writeln!(f, "part{part} = \"{HASH_PREFIX}{hash}\"")?,
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn generate_salt() -> String {
+    let mut hasher = RandomState::new().build_hasher();
+    if let Ok(now) = SystemTime::now().duration_since(SystemTime::UNIX_EPOCH) {
+        hasher.write_u128(now.as_nanos());
+    }
+
+    format!("{:016x}", hasher.finish())
+}
+
+fn parse_section(section: &str) -> Result<usize> {
+    section
+        .strip_suffix(']')
+        .and_then(|name| name.trim().strip_prefix("day"))
+        .context("Expected a section like [day1]")?
+        .parse()
+        .context("Unable to parse day number of section")
+}
+
+fn parse_entry(line: &str) -> Result<(&str, String)> {
+    let (key, value) = line
+        .split_once('=')
+        .context("Expected an entry like part1 = \"123\"")?;
+    let value = value.trim();
+    let value = match value.strip_prefix('"') {
+        Some(quoted) => quoted.strip_suffix('"').context("Unterminated string")?,
+        None => value,
+    };
+
+    Ok((key.trim(), value.to_owned()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_plain() {
+        let answers =
+            Answers::parse("[day1]\npart1 = \"54338\"\n\n[day3]\npart2 = 467835\n").unwrap();
+        assert!(answers.matches(1, 1, &Answer::from(54338)));
+        assert!(!answers.matches(1, 1, &Answer::from(54339)));
+        assert!(answers.matches(3, 2, &Answer::from(467835)));
+        assert!(!answers.contains(1, 2));
+    }
+
+    #[test]
+    fn recorded_hashes_round_trip() {
+        let mut answers = Answers::default();
+        answers.record(1, 1, &Answer::from(54338));
+        answers.record(7, 2, &Answer::from("QQQJA"));
+        assert_eq!(answers.describe(1, 1), Some("<hashed>"));
+
+        let reparsed = Answers::parse(&answers.to_string()).unwrap();
+        assert!(reparsed.matches(1, 1, &Answer::from(54338)));
+        assert!(!reparsed.matches(1, 1, &Answer::from(54339)));
+        assert!(reparsed.matches(7, 2, &Answer::from("QQQJA")));
+        assert_eq!(reparsed.to_string(), answers.to_string());
+    }
+
+    #[test]
+    fn hash_covers_day_and_part() {
+        let mut answers = Answers::default();
+        answers.record(1, 1, &Answer::from(42));
+        answers.record(1, 2, &Answer::from(42));
+        answers.record(2, 1, &Answer::from(42));
+        let text = answers.to_string();
+        let hashes = text
+            .lines()
+            .filter_map(|line| line.split_once(HASH_PREFIX))
+            .map(|(_, hash)| hash)
+            .collect::<Vec<_>>();
+        assert_eq!(hashes.len(), 3);
+        assert_ne!(hashes[0], hashes[1]);
+        assert_ne!(hashes[0], hashes[2]);
+    }
+
+    #[test]
+    fn parse_for_day_without_section() {
+        let answers = Answers::parse_for_day("part1 = 142 # from the puzzle\n", 1).unwrap();
+        assert!(answers.matches(1, 1, &Answer::from(142)));
+        assert!(Answers::parse("part1 = 142\n").is_err());
+    }
+}
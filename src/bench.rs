@@ -0,0 +1,200 @@
+use std::{
+    fmt,
+    time::{Duration, Instant},
+};
+
+use anyhow::Result;
+
+use crate::{
+    allocations::{self, AllocationStats},
+    isolated, Day,
+};
+
+/// What is being timed: parsing the input or solving one of the parts.
+#[derive(Clone, Copy)]
+pub enum Stage {
+    Parse,
+    Part(u8),
+}
+
+impl fmt::Display for Stage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Parse => f.pad("parse"),
+            Self::Part(part) => f.pad(&part.to_string()),
+        }
+    }
+}
+
+pub struct Timings {
+    pub day: usize,
+    pub stage: Stage,
+    pub samples: Result<Vec<Duration>>,
+    /// What a single run allocated, if allocations are being counted.
+    pub allocations: Option<AllocationStats>,
+}
+
+/// Parse the input of `day` `iterations` times, then run each of the given parts `iterations`
+/// times on the parsed input, and collect the wall-clock time of every run.
+///
+/// Every run is isolated as in [`Day::run_part_isolated`], so a run that panics or takes longer
+/// than `timeout` fails its stage instead of the whole benchmark, and the stage isn't run again.
+/// Opening the input file is not included in the timings of parsing. If parsing fails the parts
+/// can't be run, so only the parsing error is returned.
+pub fn bench_day(
+    day: &Day,
+    parts: &[u8],
+    iterations: usize,
+    timeout: Option<Duration>,
+) -> Vec<Timings> {
+    let mut parsed = None;
+    let mut allocations = None;
+    let parse_samples = (0..iterations)
+        .map(|_| {
+            let mut day = day.fork();
+            let (result, elapsed, stats) =
+                isolated(format!("day{} parse", day.day), timeout, move || {
+                    let input = day.input.open()?;
+                    let start = Instant::now();
+                    let (result, stats) = allocations::measure(|| day.solutions.parse(input));
+
+                    Ok((result, start.elapsed(), stats))
+                })
+                .and_then(|sample| sample)?;
+            parsed = Some(result?);
+            allocations = stats;
+
+            Ok(elapsed)
+        })
+        .collect();
+    let parse_timings = Timings {
+        day: day.day,
+        stage: Stage::Parse,
+        samples: parse_samples,
+        allocations,
+    };
+    let (Ok(_), Some(parsed)) = (&parse_timings.samples, parsed) else {
+        return vec![parse_timings];
+    };
+
+    let mut timings = vec![parse_timings];
+    timings.extend(parts.iter().map(|&part| {
+        let mut allocations = None;
+        let samples = (0..iterations)
+            .map(|_| {
+                let mut day = day.fork();
+                let parsed = parsed.clone();
+                let (result, elapsed, stats) =
+                    isolated(format!("day{} part{part}", day.day), timeout, move || {
+                        let start = Instant::now();
+                        let (result, stats) =
+                            allocations::measure(|| day.solutions.part(part, &parsed));
+
+                        (result, start.elapsed(), stats)
+                    })?;
+                result?;
+                allocations = stats;
+
+                Ok(elapsed)
+            })
+            .collect();
+
+        Timings {
+            day: day.day,
+            stage: Stage::Part(part),
+            samples,
+            allocations,
+        }
+    }));
+
+    timings
+}
+
+struct Stats {
+    min: Duration,
+    median: Duration,
+    mean: Duration,
+    p95: Duration,
+}
+
+impl Stats {
+    fn from_samples(samples: &[Duration]) -> Self {
+        let mut sorted = samples.to_vec();
+        sorted.sort_unstable();
+        let percentile = |p: usize| sorted[((sorted.len() - 1) * p).div_ceil(100)];
+
+        Self {
+            min: sorted[0],
+            median: percentile(50),
+            mean: sorted.iter().sum::<Duration>() / sorted.len() as u32,
+            p95: percentile(95),
+        }
+    }
+}
+
+/// Print the timings as a table. If allocations were counted, what a single run allocated is
+/// printed next to the timings.
+pub fn print_timings(timings: &[Timings]) {
+    let counted = timings.iter().any(|timing| timing.allocations.is_some());
+    print!(
+        "Day   Part  Runs  {:>10}  {:>10}  {:>10}  {:>10}",
+        "Min", "Median", "Mean", "p95"
+    );
+    if counted {
+        print!("  {:>8}  {:>10}  {:>10}", "Allocs", "Allocated", "Peak");
+    }
+    println!();
+
+    for timing in timings {
+        match &timing.samples {
+            Ok(samples) => {
+                let stats = Stats::from_samples(samples);
+                print!(
+                    "{:>3}  {:>5}  {:>4}  {:>10}  {:>10}  {:>10}  {:>10}",
+                    timing.day,
+                    timing.stage,
+                    samples.len(),
+                    format_duration(stats.min),
+                    format_duration(stats.median),
+                    format_duration(stats.mean),
+                    format_duration(stats.p95),
+                );
+                if let Some(allocations) = timing.allocations.filter(|_| counted) {
+                    print!(
+                        "  {:>8}  {:>10}  {:>10}",
+                        allocations.allocations,
+                        format_bytes(allocations.bytes),
+                        format_bytes(allocations.peak),
+                    );
+                }
+                println!();
+            }
+            Err(error) => println!("{:>3}  {:>5}  error: {:#}", timing.day, timing.stage, error),
+        }
+    }
+}
+
+pub fn format_bytes(bytes: u64) -> String {
+    if bytes < 1 << 10 {
+        format!("{bytes}B")
+    } else if bytes < 1 << 20 {
+        format!("{:.2}KiB", bytes as f64 / (1 << 10) as f64)
+    } else if bytes < 1 << 30 {
+        format!("{:.2}MiB", bytes as f64 / (1 << 20) as f64)
+    } else {
+        format!("{:.2}GiB", bytes as f64 / (1 << 30) as f64)
+    }
+}
+
+pub fn format_duration(duration: Duration) -> String {
+    let nanos = duration.as_nanos();
+    if nanos < 1_000 {
+        format!("{nanos}ns")
+    } else if nanos < 1_000_000 {
+        format!("{:.2}µs", nanos as f64 / 1e3)
+    } else if nanos < 1_000_000_000 {
+        format!("{:.2}ms", nanos as f64 / 1e6)
+    } else {
+        format!("{:.2}s", nanos as f64 / 1e9)
+    }
+}
@@ -0,0 +1,428 @@
+use std::{collections::HashMap, iter::Peekable, path::PathBuf, time::Duration};
+
+use anyhow::{bail, Context, Result};
+
+use advent_of_code_2023::{generate::Knobs, parse_day_selection, pool, report::Format};
+
+/// Which days and parts to run, and where to read their input from.
+pub struct Selection {
+    pub days: Vec<usize>,
+    pub parts: Vec<u8>,
+    pub input: Option<String>,
+    /// How many threads to run days and parts on.
+    pub jobs: usize,
+    /// How long a part may run before it is reported as timed out.
+    pub timeout: Option<Duration>,
+    /// Whether to collect and print what the solutions trace while solving.
+    pub trace: bool,
+}
+
+pub enum Command {
+    Run {
+        selection: Selection,
+        format: Format,
+        example: bool,
+    },
+    Bench {
+        selection: Selection,
+        iterations: usize,
+    },
+    Verify {
+        selection: Selection,
+        answers: PathBuf,
+    },
+    Record {
+        selection: Selection,
+        answers: PathBuf,
+    },
+    Watch {
+        selection: Selection,
+        interval: Duration,
+    },
+    Inputs {
+        selection: Selection,
+        directory: PathBuf,
+    },
+    History {
+        selection: Selection,
+        limit: usize,
+        threshold: f64,
+    },
+    Compare {
+        selection: Selection,
+        threshold: f64,
+    },
+    Generate {
+        day: usize,
+        seed: u64,
+        count: u64,
+        knobs: Knobs,
+        output: PathBuf,
+    },
+    Fuzz {
+        selection: Selection,
+        seed: u64,
+        cases: u64,
+        knobs: Knobs,
+    },
+    List,
+    New {
+        day: usize,
+    },
+}
+
+const USAGE: &str = "Usage:
+    advent-of-code-2023 <days> [<run options>] [--format text|json|csv] [--trace]
+    advent-of-code-2023 <days> [--part 1|2] [--timeout <s>] [--trace] --example
+    advent-of-code-2023 bench <days> [--part 1|2] [--iterations <n>] [--input <path>] [--timeout <s>]
+    advent-of-code-2023 verify [<days>] [<run options>] [--answers <path>]
+    advent-of-code-2023 record [<days>] [<run options>] [--answers <path>]
+    advent-of-code-2023 watch <day> [--part 1|2] [--input <path>] [--timeout <s>] [--interval <ms>]
+    advent-of-code-2023 inputs <day> <directory> [--part 1|2] [--jobs <n>] [--timeout <s>]
+    advent-of-code-2023 history [<days>] [--part 1|2] [--limit <n>] [--threshold <percent>]
+    advent-of-code-2023 compare [<days>] [--part 1|2] [--jobs <n>] [--timeout <s>] [--threshold <percent>]
+    advent-of-code-2023 gen <day> [--seed <n>] [--count <n>] [--size <knob>=<n>,...] [--output <directory>]
+    advent-of-code-2023 fuzz [<days>] [--part 1|2] [--seed <n>] [--cases <n>] [--size <knob>=<n>,...] [--timeout <s>]
+    advent-of-code-2023 list
+    advent-of-code-2023 new <day>
+
+<run options> are [--part 1|2] [--input <path>] [--jobs <n>] [--timeout <s>].
+
+<days> is a day number, a list of days and ranges like 1-5,7, or \"all\". Where it is optional
+it defaults to \"all\".
+--part only runs the given part of each day instead of both.
+--jobs runs the days and parts on <n> threads, one per CPU by default. The output is always in
+day and part order.
+--timeout reports parts that take longer than <s> seconds as TIMEOUT. Parts that panic are
+reported as PANIC either way.
+--answers reads the expected answers from <path> instead of answers.toml. record stores salted
+hashes of the current answers in that file.
+--input reads the puzzle input from <path> instead of puzzle-input/day#, or from stdin if <path>
+is \"-\". It can only be used when running a single day.
+--format json|csv prints one machine-readable record per day and part.
+--example runs the examples in puzzle-input/day#.example* instead of the real input, checking
+them against the expected answers in puzzle-input/day#.example*.answers if there are any.
+--trace prints the intermediate values the solutions trace while solving, after the results, or
+includes them in the JSON. Traced runs are not recorded in the history.
+watch re-runs the day whenever its input changes, and rebuilds and restarts whenever
+src/day#.rs changes, polling every <ms> milliseconds (500 by default).
+inputs runs the day on every file in <directory> and tabulates the results, checking them against
+the expected answers in <file>.answers next to each file if there are any.
+Runs on the real puzzle input are recorded in run-history.tsv. history shows the last <n> runs of
+each part (10 by default), and compare runs the days and compares them with the last recorded run.
+Both flag changed answers, and parts that got slower by more than <percent> (10 by default).
+gen writes <n> random inputs for the day (1 by default) to generated/day#/seed-#, generated from
+seeds <n>, <n>+1 and so on (1 by default). --size sets the day's knobs for the size of the inputs,
+see the day's generate function for which it has.
+fuzz checks the parts that have an oracle, a slow but obviously correct solution, against it on
+<n> generated inputs (100 by default), and prints a minimized input for parts that disagree.
+--seed and --size work as for gen, but --size only when checking a single day.
+new generates src/day#.rs, registers it and creates an empty puzzle-input/day#. It has to be run
+from the root of the repository and never overwrites existing files.";
+
+impl Command {
+    pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Self> {
+        let mut args = args.into_iter().peekable();
+        let first = args.next().context(USAGE)?;
+
+        let command = match first.as_str() {
+            "bench" => {
+                let days = parse_day_selection(&args.next().context(USAGE)?)?;
+                let mut options =
+                    Options::parse(args, &["iterations", "part", "input", "timeout"], &[])?;
+                let iterations = options
+                    .take("iterations")
+                    .map(|value| value.parse().context("Unable to parse iteration count"))
+                    .transpose()?
+                    .unwrap_or(10);
+                if iterations == 0 {
+                    bail!("Iteration count must be at least 1");
+                }
+                let selection = options.take_selection(days)?;
+
+                Self::Bench {
+                    selection,
+                    iterations,
+                }
+            }
+            command @ ("verify" | "record") => {
+                let days = optional_day_selection(&mut args)?;
+                let mut options =
+                    Options::parse(args, &["answers", "part", "input", "jobs", "timeout"], &[])?;
+                let answers = options
+                    .take("answers")
+                    .unwrap_or_else(|| "answers.toml".to_owned())
+                    .into();
+                let selection = options.take_selection(days)?;
+
+                if command == "verify" {
+                    Self::Verify { selection, answers }
+                } else {
+                    Self::Record { selection, answers }
+                }
+            }
+            "watch" => {
+                let day = args
+                    .next()
+                    .context(USAGE)?
+                    .parse()
+                    .context("Unable to parse day number")?;
+                let mut options =
+                    Options::parse(args, &["part", "input", "interval", "timeout"], &[])?;
+                let selection = options.take_selection(vec![day])?;
+                let interval = options
+                    .take("interval")
+                    .map(|value| value.parse().context("Unable to parse interval"))
+                    .transpose()?
+                    .map_or(Duration::from_millis(500), Duration::from_millis);
+
+                Self::Watch {
+                    selection,
+                    interval,
+                }
+            }
+            "inputs" => {
+                let day = args
+                    .next()
+                    .context(USAGE)?
+                    .parse()
+                    .context("Unable to parse day number")?;
+                let directory = args.next().context(USAGE)?.into();
+                let mut options = Options::parse(args, &["part", "jobs", "timeout"], &[])?;
+                let selection = options.take_selection(vec![day])?;
+
+                Self::Inputs {
+                    selection,
+                    directory,
+                }
+            }
+            command @ ("history" | "compare") => {
+                let days = optional_day_selection(&mut args)?;
+                let allowed: &[&str] = if command == "history" {
+                    &["part", "limit", "threshold"]
+                } else {
+                    &["part", "jobs", "timeout", "threshold"]
+                };
+                let mut options = Options::parse(args, allowed, &[])?;
+                let threshold = options
+                    .take("threshold")
+                    .map(|value| {
+                        value
+                            .parse::<f64>()
+                            .ok()
+                            .filter(|percent| *percent >= 0.0)
+                            .with_context(|| format!("Invalid threshold \"{value}\""))
+                    })
+                    .transpose()?
+                    .unwrap_or(10.0)
+                    / 100.0;
+                let limit = options
+                    .take("limit")
+                    .map(|value| value.parse().context("Unable to parse limit"))
+                    .transpose()?
+                    .unwrap_or(10);
+                let selection = options.take_selection(days)?;
+
+                if command == "history" {
+                    Self::History {
+                        selection,
+                        limit,
+                        threshold,
+                    }
+                } else {
+                    Self::Compare {
+                        selection,
+                        threshold,
+                    }
+                }
+            }
+            "new" => {
+                let day = args
+                    .next()
+                    .context(USAGE)?
+                    .parse()
+                    .context("Unable to parse day number")?;
+                Options::parse(args, &[], &[])?;
+                Self::New { day }
+            }
+            "gen" => {
+                let day = args
+                    .next()
+                    .context(USAGE)?
+                    .parse()
+                    .context("Unable to parse day number")?;
+                let mut options = Options::parse(args, &["seed", "count", "size", "output"], &[])?;
+                let seed = options
+                    .take("seed")
+                    .map(|value| value.parse().context("Unable to parse seed"))
+                    .transpose()?
+                    .unwrap_or(1);
+                let count = options
+                    .take("count")
+                    .map(|value| value.parse().context("Unable to parse count"))
+                    .transpose()?
+                    .unwrap_or(1);
+                let knobs = Knobs::parse(&options.take("size").unwrap_or_default())?;
+                let output = options.take("output").map_or_else(
+                    || PathBuf::from(format!("generated/day{day}")),
+                    PathBuf::from,
+                );
+
+                Self::Generate {
+                    day,
+                    seed,
+                    count,
+                    knobs,
+                    output,
+                }
+            }
+            "fuzz" => {
+                let days = optional_day_selection(&mut args)?;
+                let mut options =
+                    Options::parse(args, &["part", "seed", "cases", "size", "timeout"], &[])?;
+                let seed = options
+                    .take("seed")
+                    .map(|value| value.parse().context("Unable to parse seed"))
+                    .transpose()?
+                    .unwrap_or(1);
+                let cases = options
+                    .take("cases")
+                    .map(|value| value.parse().context("Unable to parse number of cases"))
+                    .transpose()?
+                    .unwrap_or(100);
+                let size = options.take("size");
+                if size.is_some() && days.len() != 1 {
+                    bail!("--size can only be used when checking a single day");
+                }
+                let knobs = Knobs::parse(&size.unwrap_or_default())?;
+                let selection = options.take_selection(days)?;
+
+                Self::Fuzz {
+                    selection,
+                    seed,
+                    cases,
+                    knobs,
+                }
+            }
+            "list" => {
+                Options::parse(args, &[], &[])?;
+                Self::List
+            }
+            selection => {
+                let days = parse_day_selection(selection)?;
+                let mut options = Options::parse(
+                    args,
+                    &["part", "input", "format", "jobs", "timeout"],
+                    &["example", "trace"],
+                )?;
+                let mut selection = options.take_selection(days)?;
+                selection.trace = options.flag("trace");
+                let format = options
+                    .take("format")
+                    .map(|format| format.parse())
+                    .transpose()?
+                    .unwrap_or(Format::Text);
+                let example = options.flag("example");
+                if example && (selection.input.is_some() || !matches!(format, Format::Text)) {
+                    bail!("--example can't be combined with --input or --format");
+                }
+                if selection.trace && matches!(format, Format::Csv) {
+                    bail!("--trace can't be combined with --format csv");
+                }
+
+                Self::Run {
+                    selection,
+                    format,
+                    example,
+                }
+            }
+        };
+
+        Ok(command)
+    }
+}
+
+/// Parse the day selection if the next argument is one rather than an option, otherwise select
+/// all days.
+fn optional_day_selection(args: &mut Peekable<impl Iterator<Item = String>>) -> Result<Vec<usize>> {
+    let selection = args.next_if(|arg| !arg.starts_with("--"));
+    parse_day_selection(selection.as_deref().unwrap_or("all"))
+}
+
+/// `--name value` pairs and `--flag`s following the positional arguments of a command.
+struct Options(HashMap<String, String>);
+
+impl Options {
+    fn parse(
+        mut args: impl Iterator<Item = String>,
+        allowed: &[&str],
+        flags: &[&str],
+    ) -> Result<Self> {
+        let mut values = HashMap::new();
+        while let Some(arg) = args.next() {
+            let name = arg
+                .strip_prefix("--")
+                .filter(|name| allowed.contains(name) || flags.contains(name))
+                .with_context(|| format!("Unknown argument \"{arg}\"\n\n{USAGE}"))?;
+            let value = if flags.contains(&name) {
+                String::new()
+            } else {
+                args.next()
+                    .with_context(|| format!("Missing value for {arg}"))?
+            };
+            values.insert(name.to_owned(), value);
+        }
+
+        Ok(Self(values))
+    }
+
+    fn take(&mut self, name: &str) -> Option<String> {
+        self.0.remove(name)
+    }
+
+    fn flag(&mut self, name: &str) -> bool {
+        self.take(name).is_some()
+    }
+
+    fn take_selection(&mut self, days: Vec<usize>) -> Result<Selection> {
+        let parts = match self.take("part").as_deref() {
+            None => vec![1, 2],
+            Some("1") => vec![1],
+            Some("2") => vec![2],
+            Some(part) => bail!("Invalid part \"{part}\", expected 1 or 2"),
+        };
+        let input = self.take("input");
+        if input.is_some() && days.len() != 1 {
+            bail!("--input can only be used when running a single day");
+        }
+
+        let jobs = self
+            .take("jobs")
+            .map(|value| value.parse().context("Unable to parse number of jobs"))
+            .transpose()?
+            .unwrap_or_else(pool::default_threads);
+        if jobs == 0 {
+            bail!("Number of jobs must be at least 1");
+        }
+
+        let timeout = self
+            .take("timeout")
+            .map(|value| {
+                value
+                    .parse()
+                    .ok()
+                    .and_then(|seconds| Duration::try_from_secs_f64(seconds).ok())
+                    .with_context(|| format!("Invalid timeout \"{value}\""))
+            })
+            .transpose()?;
+
+        Ok(Selection {
+            days,
+            parts,
+            input,
+            jobs,
+            timeout,
+            trace: false,
+        })
+    }
+}
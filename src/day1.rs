@@ -1,8 +1,13 @@
 use std::io::BufRead;
 
-use anyhow::{Context, Result};
+use anyhow::Result;
 
-use crate::{Input, Solutions};
+use crate::{
+    generate::{Knobs, Rng},
+    parse::ParseError,
+    trace::{Event, Trace},
+    Answer, Input, Solutions,
+};
 
 const DIGIT_TABLE: [(&str, u32); 18] = [
     ("1", 1),
@@ -32,48 +37,145 @@ fn parse_digit(slice: &str) -> Option<u32> {
         .map(|(_, digit)| digit)
 }
 
-fn first_digit(s: &str) -> Option<u32> {
+/// The first digit in `s`, either as a digit or spelled out as a word.
+pub fn first_digit(s: &str) -> Option<u32> {
     (0..s.len()).find_map(|start_index| parse_digit(&s[start_index..]))
 }
 
-fn last_digit(s: &str) -> Option<u32> {
+/// The last digit in `s`, either as a digit or spelled out as a word.
+pub fn last_digit(s: &str) -> Option<u32> {
     (0..s.len())
         .rev()
         .find_map(|start_index| parse_digit(&s[start_index..]))
 }
 
+fn no_digits(line: &str) -> ParseError {
+    ParseError::at(line, line, "No digits in line")
+}
+
 #[derive(Default)]
-pub struct Day1;
+pub struct Day1 {
+    trace: Trace,
+}
 
 impl Solutions for Day1 {
-    fn part1(&mut self, input: Input) -> Result<String> {
-        let solution: u32 = input
-            .lines()
-            .map(|line| {
-                let line = line?;
+    /// The lines of the document. The parts disagree on what counts as a digit, so this is as far
+    /// as parsing can go.
+    type Parsed = Vec<String>;
+
+    fn parse(&mut self, input: Input<'_>) -> Result<Self::Parsed> {
+        Ok(input.lines().collect::<Result<_, _>>()?)
+    }
+
+    fn part1(&mut self, lines: &Self::Parsed) -> Result<Answer> {
+        let solution: u64 = lines
+            .iter()
+            .enumerate()
+            .map(|(index, line)| {
                 let mut digits = line.chars().filter_map(|c| c.to_digit(10));
-                let first = digits.next().context("No digits in input")?;
-                let last = digits.last().unwrap_or(first);
+                let first = digits
+                    .next()
+                    .ok_or_else(|| no_digits(line).on_line(index + 1))?;
+                let last = digits.next_back().unwrap_or(first);
+                let value = first * 10 + last;
+                self.trace.emit(|| {
+                    Event::new("calibration value")
+                        .field("line", index + 1)
+                        .field("value", value)
+                });
 
-                Ok(first * 10 + last)
+                Ok(u64::from(value))
             })
             .sum::<Result<_>>()?;
 
-        Ok(solution.to_string())
+        Ok(solution.into())
     }
 
-    fn part2(&mut self, input: Input) -> Result<String> {
-        let solution: u32 = input
-            .lines()
-            .map(|line| {
-                let line = line?;
-                let first = first_digit(&line).context("No digits in input")?;
-                let last = last_digit(&line).unwrap_or(first);
+    fn part2(&mut self, lines: &Self::Parsed) -> Result<Answer> {
+        let solution: u64 = lines
+            .iter()
+            .enumerate()
+            .map(|(index, line)| {
+                let first = first_digit(line).ok_or_else(|| no_digits(line).on_line(index + 1))?;
+                let last = last_digit(line).unwrap_or(first);
+                let value = first * 10 + last;
+                self.trace.emit(|| {
+                    Event::new("calibration value")
+                        .field("line", index + 1)
+                        .field("value", value)
+                });
 
-                Ok(first * 10 + last)
+                Ok(u64::from(value))
             })
             .sum::<Result<_>>()?;
 
-        Ok(solution.to_string())
+        Ok(solution.into())
+    }
+
+    fn set_trace(&mut self, trace: Trace) {
+        self.trace = trace;
+    }
+
+    /// Lines of letters with digits and spelled out digits mixed in, and at least one digit.
+    /// Knobs: `lines`, and `length`, the number of letters and digits per line.
+    fn generate(&self, rng: &mut Rng, knobs: &mut Knobs) -> Option<String> {
+        let lines = knobs.get("lines", 1000);
+        let length = knobs.get("length", 20).max(1) as usize;
+
+        let mut input = String::new();
+        for _ in 0..lines {
+            let mut pieces = (0..length)
+                .map(|_| {
+                    if rng.chance(0.1) {
+                        rng.range(1..=9).to_string()
+                    } else if rng.chance(0.1) {
+                        DIGIT_TABLE[rng.range(9..=17) as usize].0.to_owned()
+                    } else {
+                        char::from(b'a' + rng.range(0..=25) as u8).to_string()
+                    }
+                })
+                .collect::<Vec<_>>();
+            let digit = rng.index(length);
+            pieces[digit] = rng.range(1..=9).to_string();
+
+            input.push_str(&pieces.concat());
+            input.push('\n');
+        }
+
+        Some(input)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "\
+1abc2
+pqr3stu8vwx
+a1b2c3d4e5f
+treb7uchet
+";
+
+    const PART2_EXAMPLE: &str = "\
+two1nine
+eightwothree
+abcone2threexyz
+xtwone3four
+4nineeightseven2
+zoneight234
+7pqrstsixteen
+";
+
+    #[test]
+    fn part1_example() {
+        let parsed = Day1::default().parse(EXAMPLE.into()).unwrap();
+        assert_eq!(Day1::default().part1(&parsed).unwrap(), Answer::from(142));
+    }
+
+    #[test]
+    fn part2_example() {
+        let parsed = Day1::default().parse(PART2_EXAMPLE.into()).unwrap();
+        assert_eq!(Day1::default().part2(&parsed).unwrap(), Answer::from(281));
     }
 }
@@ -1,22 +1,26 @@
 use std::{io::BufRead, str::FromStr};
 
-use anyhow::{bail, Context, Error, Result};
+use anyhow::{Context, Result};
 
-use crate::{Input, Solutions};
+use crate::{
+    generate::{Knobs, Rng},
+    parse::{parse_at, ParseError},
+    Answer, Input, Solutions,
+};
 
-struct Game {
-    id: u32,
-    picks: Vec<Pick>,
+pub struct Game {
+    pub id: u32,
+    pub picks: Vec<Pick>,
 }
 
-struct Pick {
-    red: u32,
-    green: u32,
-    blue: u32,
+pub struct Pick {
+    pub red: u32,
+    pub green: u32,
+    pub blue: u32,
 }
 
 impl Game {
-    fn is_possible(&self) -> bool {
+    pub fn is_possible(&self) -> bool {
         const QUERY_RED: u32 = 12;
         const QUERY_GREEN: u32 = 13;
         const QUERY_BLUE: u32 = 14;
@@ -26,7 +30,7 @@ impl Game {
         })
     }
 
-    fn power(&self) -> u32 {
+    pub fn power(&self) -> u64 {
         let mut red = 0;
         let mut green = 0;
         let mut blue = 0;
@@ -37,19 +41,24 @@ impl Game {
             blue = blue.max(pick.blue);
         }
 
-        red * green * blue
+        u64::from(red) * u64::from(green) * u64::from(blue)
     }
 }
 
 impl FromStr for Game {
-    type Err = Error;
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let mut split = s.split(": ");
-        let id_part = split.next().context("Unable to read ID part")?;
-        let data_part = split.next().context("Unable to read data part")?;
+        let id_part = split.next().unwrap_or_default();
+        let data_part = split
+            .next()
+            .ok_or_else(|| ParseError::at_end(s, "Unable to read data part"))?;
 
-        let id = id_part[5..].parse().context("Unable to parse ID")?;
+        let id_str = id_part
+            .strip_prefix("Game ")
+            .ok_or_else(|| ParseError::at(s, id_part, "Expected \"Game <ID>\""))?;
+        let id = parse_at(s, id_str, "ID")?;
 
         let picks = data_part
             .split("; ")
@@ -59,12 +68,11 @@ impl FromStr for Game {
                 let mut blue = 0;
                 for cube_str in hand_str.split(", ") {
                     let mut split = cube_str.split(' ');
-                    let amount = split
+                    let amount_str = split.next().unwrap_or_default();
+                    let amount = parse_at::<u32>(s, amount_str, "amount")?;
+                    let color = split
                         .next()
-                        .context("Unable to read amount part")?
-                        .parse::<u32>()
-                        .context("Unable to parse amount")?;
-                    let color = split.next().context("Unable to read color part")?;
+                        .ok_or_else(|| ParseError::at(s, cube_str, "Unable to read color part"))?;
                     if color == "red" {
                         red += amount;
                     } else if color == "blue" {
@@ -72,14 +80,13 @@ impl FromStr for Game {
                     } else if color == "green" {
                         green += amount;
                     } else {
-                        bail!("Invalid color");
+                        return Err(ParseError::at(s, color, "Invalid color"));
                     }
                 }
 
                 Ok(Pick { red, green, blue })
             })
-            .collect::<Result<Vec<_>>>()
-            .context("Unable to parse cubes")?;
+            .collect::<Result<Vec<_>, _>>()?;
 
         Ok(Game { id, picks })
     }
@@ -89,34 +96,86 @@ impl FromStr for Game {
 pub struct Day2;
 
 impl Solutions for Day2 {
-    fn part1(&mut self, input: Input) -> Result<String> {
-        let solution: u32 = input
+    type Parsed = Vec<Game>;
+
+    fn parse(&mut self, input: Input<'_>) -> Result<Self::Parsed> {
+        input
             .lines()
-            .map(|line| {
-                let line = line?;
-                let game: Game = line.parse().context("Unable to parse game")?;
-                Ok(if game.is_possible() {
-                    Some(game.id)
-                } else {
-                    None
-                })
+            .enumerate()
+            .map(|(index, line)| {
+                line?
+                    .parse()
+                    .map_err(|error: ParseError| error.on_line(index + 1))
+                    .context("Unable to parse game")
             })
-            .filter_map(Result::transpose)
-            .sum::<Result<_>>()?;
+            .collect()
+    }
 
-        Ok(solution.to_string())
+    fn part1(&mut self, games: &Self::Parsed) -> Result<Answer> {
+        let solution: u64 = games
+            .iter()
+            .filter(|game| game.is_possible())
+            .map(|game| u64::from(game.id))
+            .sum();
+
+        Ok(solution.into())
     }
 
-    fn part2(&mut self, input: Input) -> Result<String> {
-        let solution: u32 = input
-            .lines()
-            .map(|line| {
-                let line = line?;
-                let game: Game = line.parse().context("Unable to parse game")?;
-                Ok(game.power())
-            })
-            .sum::<Result<_>>()?;
+    fn part2(&mut self, games: &Self::Parsed) -> Result<Answer> {
+        let solution: u64 = games.iter().map(Game::power).sum();
+
+        Ok(solution.into())
+    }
+
+    /// Games numbered from 1, each with up to `picks` picks of up to `cubes` cubes of each color.
+    /// Knobs: `games`, `picks` and `cubes`.
+    fn generate(&self, rng: &mut Rng, knobs: &mut Knobs) -> Option<String> {
+        let games = knobs.get("games", 100);
+        let picks = knobs.get("picks", 6).max(1);
+        let cubes = knobs.get("cubes", 20).max(1);
+
+        let mut input = String::new();
+        for id in 1..=games {
+            let picks = (0..rng.range(1..=picks))
+                .map(|_| {
+                    let mut colors = ["red", "green", "blue"];
+                    rng.shuffle(&mut colors);
+                    colors[..rng.range(1..=3) as usize]
+                        .iter()
+                        .map(|color| format!("{} {color}", rng.range(1..=cubes)))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                })
+                .collect::<Vec<_>>();
+
+            input.push_str(&format!("Game {id}: {}\n", picks.join("; ")));
+        }
+
+        Some(input)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "\
+Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green
+Game 2: 1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue
+Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red
+Game 4: 1 green, 3 red, 6 blue; 3 green, 6 red; 3 green, 15 blue, 14 red
+Game 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green
+";
+
+    #[test]
+    fn part1_example() {
+        let parsed = Day2.parse(EXAMPLE.into()).unwrap();
+        assert_eq!(Day2.part1(&parsed).unwrap(), Answer::from(8));
+    }
 
-        Ok(solution.to_string())
+    #[test]
+    fn part2_example() {
+        let parsed = Day2.parse(EXAMPLE.into()).unwrap();
+        assert_eq!(Day2.part2(&parsed).unwrap(), Answer::from(2286));
     }
 }
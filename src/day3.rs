@@ -1,23 +1,37 @@
-use std::{cell::RefCell, io::BufRead, rc::Rc};
+use std::io::BufRead;
 
 use anyhow::Result;
 use itertools::Itertools;
 
-use crate::{Input, Solutions};
+use crate::{
+    generate::{Knobs, Rng},
+    parse::ParseError,
+    Answer, Input, Solutions,
+};
 
 #[derive(Debug, Default)]
-struct Schematic {
-    grid: Vec<Tile>,
-    numbers: Vec<Rc<RefCell<NumberTile>>>,
-    width: usize,
+pub struct Schematic {
+    pub grid: Vec<Tile>,
+    pub numbers: Vec<NumberTile>,
+    pub width: usize,
 }
 
 impl Schematic {
-    fn read(input: Input) -> Result<Self> {
+    pub fn read(input: Input<'_>) -> Result<Self> {
         let mut this = Self::default();
 
-        for line in input.lines() {
+        for (index, line) in input.lines().enumerate() {
             let line = line?;
+            if !this.grid.is_empty() && line.len() != this.width {
+                let excess = line.get(this.width..).unwrap_or_default();
+                return Err(ParseError::at(
+                    &line,
+                    excess,
+                    format!("Expected all lines to be {} wide", this.width),
+                )
+                .on_line(index + 1)
+                .into());
+            }
 
             let mut parsing_number = None;
             for char in line.chars() {
@@ -27,22 +41,21 @@ impl Schematic {
                         Tile::Empty
                     }
                     '0'..='9' => {
-                        let number_tile = parsing_number.get_or_insert_with(|| {
-                            let number_tile = Rc::new(RefCell::new(NumberTile {
+                        let number_index = *parsing_number.get_or_insert_with(|| {
+                            this.numbers.push(NumberTile {
                                 value: 0,
                                 start: this.grid.len(),
                                 length: 0,
-                            }));
-                            this.numbers.push(number_tile.clone());
-                            number_tile
+                            });
+                            this.numbers.len() - 1
                         });
-                        let mut tile_data_ref = number_tile.borrow_mut();
+                        let number_tile = &mut this.numbers[number_index];
 
-                        tile_data_ref.value *= 10;
-                        tile_data_ref.value += char as u32 - '0' as u32;
-                        tile_data_ref.length += 1;
+                        number_tile.value *= 10;
+                        number_tile.value += u64::from(char as u32 - '0' as u32);
+                        number_tile.length += 1;
 
-                        Tile::Number(number_tile.clone())
+                        Tile::Number(number_index)
                     }
                     '*' => {
                         parsing_number = None;
@@ -62,19 +75,19 @@ impl Schematic {
         Ok(this)
     }
 
-    fn height(&self) -> usize {
+    pub fn height(&self) -> usize {
         self.grid.len() / self.width
     }
 
-    fn index_to_coords(&self, index: usize) -> (usize, usize) {
+    pub fn index_to_coords(&self, index: usize) -> (usize, usize) {
         (index % self.width, index / self.width)
     }
 
-    fn coords_to_index(&self, x: usize, y: usize) -> usize {
+    pub fn coords_to_index(&self, x: usize, y: usize) -> usize {
         x + y * self.width
     }
 
-    fn adjacent_indices(&self, index: usize) -> Vec<usize> {
+    pub fn adjacent_indices(&self, index: usize) -> Vec<usize> {
         let (x, y) = self.index_to_coords(index);
 
         let mut indices = Vec::new();
@@ -89,22 +102,23 @@ impl Schematic {
 }
 
 #[derive(Debug)]
-enum Tile {
+pub enum Tile {
     Empty,
-    Number(Rc<RefCell<NumberTile>>),
+    /// Part of the number at this index in [`Schematic::numbers`]
+    Number(usize),
     Gear,
     Unknown,
 }
 
 #[derive(Debug)]
-struct NumberTile {
-    value: u32,
-    start: usize,
-    length: usize,
+pub struct NumberTile {
+    pub value: u64,
+    pub start: usize,
+    pub length: usize,
 }
 
 impl NumberTile {
-    fn adjacent_indices(&self, schematic: &Schematic) -> Vec<usize> {
+    pub fn adjacent_indices(&self, schematic: &Schematic) -> Vec<usize> {
         let (start_x, start_y) = schematic.index_to_coords(self.start);
         let (end_x, end_y) = schematic.index_to_coords(self.start + self.length - 1);
         assert_eq!(start_y, end_y);
@@ -119,10 +133,10 @@ impl NumberTile {
         indices
     }
 
-    fn is_part_number(&self, schematic: &Schematic) -> bool {
+    pub fn is_part_number(&self, schematic: &Schematic) -> bool {
         self.adjacent_indices(schematic)
             .into_iter()
-            .any(|index| matches!(schematic.grid[index], Tile::Unknown))
+            .any(|index| matches!(schematic.grid[index], Tile::Gear | Tile::Unknown))
     }
 }
 
@@ -130,27 +144,30 @@ impl NumberTile {
 pub struct Day3;
 
 impl Solutions for Day3 {
-    fn part1(&mut self, input: Input) -> Result<String> {
-        let schematic = Schematic::read(input)?;
-        let solution: u32 = schematic
+    type Parsed = Schematic;
+
+    fn parse(&mut self, input: Input<'_>) -> Result<Self::Parsed> {
+        Schematic::read(input)
+    }
+
+    fn part1(&mut self, schematic: &Self::Parsed) -> Result<Answer> {
+        let solution: u64 = schematic
             .numbers
             .iter()
             .filter_map(|number_tile| {
-                let tile_data_ref = number_tile.borrow();
-                if tile_data_ref.is_part_number(&schematic) {
-                    Some(tile_data_ref.value)
+                if number_tile.is_part_number(schematic) {
+                    Some(number_tile.value)
                 } else {
                     None
                 }
             })
             .sum();
 
-        Ok(solution.to_string())
+        Ok(solution.into())
     }
 
-    fn part2(&mut self, input: Input) -> Result<String> {
-        let schematic = Schematic::read(input)?;
-        let solution: u32 = schematic
+    fn part2(&mut self, schematic: &Self::Parsed) -> Result<Answer> {
+        let solution: u64 = schematic
             .grid
             .iter()
             .enumerate()
@@ -164,20 +181,87 @@ impl Solutions for Day3 {
                     .into_iter()
                     .map(|adjacent_index| &schematic.grid[adjacent_index])
                     .filter_map(|tile| match tile {
-                        Tile::Number(number_tile) => Some(number_tile),
+                        Tile::Number(number_index) => Some(*number_index),
                         _ => None,
                     })
-                    .unique_by(|rc| Rc::as_ptr(rc))
-                    .map(|number_tile| number_tile.borrow().value)
+                    .unique()
+                    .map(|number_index| schematic.numbers[number_index].value)
                     .collect::<Vec<_>>();
                 if adjacent_numbers.len() != 2 {
                     return None;
                 }
 
-                Some(adjacent_numbers.into_iter().product::<u32>())
+                Some(adjacent_numbers.into_iter().product::<u64>())
             })
             .sum();
 
-        Ok(solution.to_string())
+        Ok(solution.into())
+    }
+
+    /// A grid of numbers of 1 to 3 digits and symbols scattered over empty space. Knobs: `width`,
+    /// `height`, and the percentage of cells where a number starts, `numbers`, or that hold a
+    /// symbol, `symbols`.
+    fn generate(&self, rng: &mut Rng, knobs: &mut Knobs) -> Option<String> {
+        const SYMBOLS: &[u8] = b"*#+$/@%=&-";
+
+        let width = knobs.get("width", 140).max(1) as usize;
+        let height = knobs.get("height", 140);
+        let numbers = knobs.get("numbers", 10) as f64 / 100.0;
+        let symbols = knobs.get("symbols", 5) as f64 / 100.0;
+
+        let mut input = String::new();
+        for _ in 0..height {
+            let mut line = Vec::with_capacity(width);
+            while line.len() < width {
+                let length = rng.range(1..=3) as usize;
+                if line.len() + length <= width && rng.chance(numbers) {
+                    line.push(b'1' + rng.range(0..=8) as u8);
+                    line.extend((1..length).map(|_| b'0' + rng.range(0..=9) as u8));
+                    // Keeps the number from running into the next one
+                    if line.len() < width {
+                        line.push(b'.');
+                    }
+                } else if rng.chance(symbols) {
+                    line.push(*rng.choose(SYMBOLS));
+                } else {
+                    line.push(b'.');
+                }
+            }
+
+            input.push_str(&String::from_utf8(line).ok()?);
+            input.push('\n');
+        }
+
+        Some(input)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "\
+467..114..
+...*......
+..35..633.
+......#...
+617*......
+.....+.58.
+..592.....
+......755.
+...$.*....
+.664.598..
+";
+
+    #[test]
+    fn part1_example() {
+        let parsed = Day3.parse(EXAMPLE.into()).unwrap();
+        assert_eq!(Day3.part1(&parsed).unwrap(), Answer::from(4361));
+    }
+
+    #[test]
+    fn part2_example() {
+        let parsed = Day3.parse(EXAMPLE.into()).unwrap();
+        assert_eq!(Day3.part2(&parsed).unwrap(), Answer::from(467835));
     }
 }
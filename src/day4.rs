@@ -3,13 +3,18 @@ use std::{collections::HashMap, io::BufRead, str::FromStr};
 use anyhow::{Context, Error, Result};
 use regex::Regex;
 
-use crate::{Input, Solutions};
+use crate::{
+    generate::{Knobs, Rng},
+    parse::{parse_at, with_line_number, ParseError},
+    trace::{Event, Trace},
+    Answer, Input, Solutions,
+};
 
 #[derive(Debug)]
-struct Card {
-    id: u32,
-    numbers: Vec<u32>,
-    winning_numbers: Vec<u32>,
+pub struct Card {
+    pub id: u32,
+    pub numbers: Vec<u32>,
+    pub winning_numbers: Vec<u32>,
 }
 
 impl FromStr for Card {
@@ -21,26 +26,27 @@ impl FromStr for Card {
         let number_list_divider = Regex::new(r" *\| *")?;
 
         let mut split = card_id_divider.split(s);
-        let id_part = split.next().context("Unable to read card ID")?;
-        let id = whitespace
+        let id_part = split.next().unwrap_or_default();
+        let id_str = whitespace
             .split(id_part)
             .nth(1)
-            .context("Unable to parse card ID")?
-            .parse()
-            .context("Unable to parse card ID as number")?;
-        let data_part = split.next().context("Unable to read card data")?;
+            .ok_or_else(|| ParseError::at(s, id_part, "Expected \"Card <ID>\""))?;
+        let id = parse_at(s, id_str, "card ID")?;
+        let data_part = split
+            .next()
+            .ok_or_else(|| ParseError::at_end(s, "Unable to read card data"))?;
         let mut split = number_list_divider.split(data_part);
         let numbers = whitespace
-            .split(split.next().context("Unable to read card numbers")?)
-            .map(str::parse)
+            .split(split.next().unwrap_or_default())
+            .map(|number| parse_at(s, number, "card number"))
             .collect::<Result<Vec<_>, _>>()?;
         let winning_numbers = whitespace
             .split(
                 split
                     .next()
-                    .context("Unable to read card winning numbers")?,
+                    .ok_or_else(|| ParseError::at_end(s, "Unable to read card winning numbers"))?,
             )
-            .map(str::parse)
+            .map(|number| parse_at(s, number, "winning number"))
             .collect::<Result<Vec<_>, _>>()?;
 
         Ok(Self {
@@ -52,7 +58,7 @@ impl FromStr for Card {
 }
 
 impl Card {
-    fn count_matches(&self) -> u32 {
+    pub fn count_matches(&self) -> u32 {
         self.numbers
             .iter()
             .filter(|number| self.winning_numbers.contains(number))
@@ -61,42 +67,139 @@ impl Card {
 }
 
 #[derive(Default)]
-pub struct Day4;
+pub struct Day4 {
+    trace: Trace,
+}
 
 impl Solutions for Day4 {
-    fn part1(&mut self, input: Input) -> Result<String> {
-        let solution: u32 = input
+    type Parsed = Vec<Card>;
+
+    fn parse(&mut self, input: Input<'_>) -> Result<Self::Parsed> {
+        input
             .lines()
-            .map(|line| {
-                let card: Card = line?.parse()?;
-                let matches = card.count_matches();
+            .enumerate()
+            .map(|(index, line)| {
+                line?
+                    .parse()
+                    .map_err(|error| with_line_number(error, index + 1))
+            })
+            .collect()
+    }
 
-                Ok(if matches == 0 { 0 } else { 1 << (matches - 1) })
+    fn part1(&mut self, cards: &Self::Parsed) -> Result<Answer> {
+        let solution: u64 = cards
+            .iter()
+            .map(|card| {
+                let matches = card.count_matches();
+                self.trace.emit(|| {
+                    Event::new("card")
+                        .field("id", card.id)
+                        .field("matches", matches)
+                });
+                if matches == 0 {
+                    Ok(0)
+                } else {
+                    1u64.checked_shl(matches - 1)
+                        .with_context(|| format!("Card {} is worth too many points", card.id))
+                }
             })
             .sum::<Result<_>>()?;
 
-        Ok(solution.to_string())
+        Ok(solution.into())
     }
 
-    fn part2(&mut self, input: Input) -> Result<String> {
-        let matches_by_card_id = input.lines().map(|line| {
-            let card: Card = line?.parse()?;
+    fn part2(&mut self, cards: &Self::Parsed) -> Result<Answer> {
+        let mut copies_by_card_id = HashMap::new();
+        for card in cards {
             let matches = card.count_matches();
+            let copies = *copies_by_card_id.entry(card.id).or_insert(1u64);
+            for i in 1..=matches {
+                *copies_by_card_id.entry(card.id + i).or_insert(1) += copies;
+            }
+        }
 
-            Ok::<_, Error>((card.id, matches))
-        });
+        let solution: u64 = copies_by_card_id.into_values().sum();
 
-        let mut copies_by_card_id = HashMap::new();
-        for result in matches_by_card_id {
-            let (card_id, matches) = result?;
-            let copies = *copies_by_card_id.entry(card_id).or_insert(1);
-            for i in 1..=matches {
-                *copies_by_card_id.entry(card_id + i).or_insert(1) += copies;
+        Ok(solution.into())
+    }
+
+    fn set_trace(&mut self, trace: Trace) {
+        self.trace = trace;
+    }
+
+    /// Cards numbered from 1 with `winning` winning numbers and `numbers` numbers you have, all
+    /// distinct and up to `max`. No card wins copies of cards past the end of the table. Knobs:
+    /// `cards`, `winning`, `numbers` and `max`.
+    fn generate(&self, rng: &mut Rng, knobs: &mut Knobs) -> Option<String> {
+        let cards = knobs.get("cards", 200);
+        let winning = knobs.get("winning", 10) as usize;
+        let numbers = knobs.get("numbers", 25) as usize;
+        let max = knobs.get("max", 99).max((winning + numbers) as u64);
+        let id_width = cards.to_string().len();
+        let number_width = max.to_string().len();
+
+        let format = |numbers: &[u64]| {
+            numbers
+                .iter()
+                .map(|number| format!("{number:>number_width$}"))
+                .collect::<Vec<_>>()
+                .join(" ")
+        };
+
+        let mut input = String::new();
+        for id in 1..=cards {
+            let mut pool = Vec::new();
+            while pool.len() < winning + numbers {
+                let number = rng.range(1..=max);
+                if !pool.contains(&number) {
+                    pool.push(number);
+                }
             }
+            let (winning_numbers, others) = pool.split_at(winning);
+            // Skewed towards few matches, since the number of copies grows exponentially with
+            // them, and would overflow in part 2 if they were uniform
+            let most = winning.min(numbers).min((cards - id) as usize) as u64;
+            let matches = if rng.chance(0.5) {
+                0
+            } else {
+                rng.range(0..=most).min(rng.range(0..=most)) as usize
+            };
+            let mut your_numbers = [&winning_numbers[..matches], &others[matches..]].concat();
+            rng.shuffle(&mut your_numbers);
+
+            input.push_str(&format!(
+                "Card {id:>id_width$}: {} | {}\n",
+                format(winning_numbers),
+                format(&your_numbers)
+            ));
         }
 
-        let solution: u32 = copies_by_card_id.into_values().sum();
+        Some(input)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "\
+Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53
+Card 2: 13 32 20 16 61 | 61 30 68 82 17 32 24 19
+Card 3:  1 21 53 59 44 | 69 82 63 72 16 21 14  1
+Card 4: 41 92 73 84 69 | 59 84 76 51 58  5 54 83
+Card 5: 87 83 26 28 32 | 88 30 70 12 93 22 82 36
+Card 6: 31 18 13 56 72 | 74 77 10 23 35 67 36 11
+";
+
+    #[test]
+    fn part1_example() {
+        let parsed = Day4::default().parse(EXAMPLE.into()).unwrap();
+        assert_eq!(Day4::default().part1(&parsed).unwrap(), Answer::from(13));
+    }
 
-        Ok(solution.to_string())
+    #[test]
+    fn part2_example() {
+        let parsed = Day4::default().parse(EXAMPLE.into()).unwrap();
+        assert_eq!(Day4::default().part2(&parsed).unwrap(), Answer::from(30));
     }
 }
@@ -1,36 +1,52 @@
 use std::{io::BufRead, str::FromStr};
 
-use anyhow::{Context, Error, Result};
+use anyhow::{Context, Result};
 use itertools::Itertools;
 
-use crate::{Input, Solutions};
+use crate::{
+    generate::{Knobs, Rng},
+    parse::{parse_at, ParseError},
+    trace::{Event, Trace},
+    Answer, Input, Solutions,
+};
 
-struct Seeds {
-    seeds: Vec<u64>,
+/// The names of the maps of an almanac, in order.
+const MAP_NAMES: [&str; 7] = [
+    "seed-to-soil",
+    "soil-to-fertilizer",
+    "fertilizer-to-water",
+    "water-to-light",
+    "light-to-temperature",
+    "temperature-to-humidity",
+    "humidity-to-location",
+];
+
+pub struct Seeds {
+    pub seeds: Vec<u64>,
 }
 
-struct Almanac {
-    seed_to_soil: Map,
-    soil_to_fertilizer: Map,
-    fertilizer_to_water: Map,
-    water_to_light: Map,
-    light_to_temperature: Map,
-    temperature_to_humidity: Map,
-    humidity_to_location: Map,
+pub struct Almanac {
+    pub seed_to_soil: Map,
+    pub soil_to_fertilizer: Map,
+    pub fertilizer_to_water: Map,
+    pub water_to_light: Map,
+    pub light_to_temperature: Map,
+    pub temperature_to_humidity: Map,
+    pub humidity_to_location: Map,
 }
 
-struct Map {
-    ranges: Vec<MapRange>,
+pub struct Map {
+    pub ranges: Vec<MapRange>,
 }
 
-struct MapRange {
-    destination_start: u64,
-    source_start: u64,
-    length: u64,
+pub struct MapRange {
+    pub destination_start: u64,
+    pub source_start: u64,
+    pub length: u64,
 }
 
 impl Map {
-    fn map(&self, source: u64) -> u64 {
+    pub fn map(&self, source: u64) -> u64 {
         self.ranges
             .iter()
             .find(|range| {
@@ -42,20 +58,24 @@ impl Map {
 }
 
 impl FromStr for Seeds {
-    type Err = Error;
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let seeds = s
+            .strip_prefix("seeds: ")
+            .ok_or_else(|| ParseError::at(s, s, "Expected \"seeds: \""))?;
+
         Ok(Seeds {
-            seeds: s[7..]
+            seeds: seeds
                 .split(' ')
-                .map(|s| Ok(s.parse()?))
-                .collect::<Result<Vec<_>>>()?,
+                .map(|seed| parse_at(s, seed, "seed"))
+                .collect::<Result<Vec<_>, _>>()?,
         })
     }
 }
 
 impl Seeds {
-    fn expand_ranges(&self) -> impl Iterator<Item = u64> + '_ {
+    pub fn expand_ranges(&self) -> impl Iterator<Item = u64> + '_ {
         self.seeds
             .iter()
             .copied()
@@ -65,24 +85,18 @@ impl Seeds {
 }
 
 impl Almanac {
-    fn parse(lines: &mut impl Iterator<Item = Result<String>>) -> Result<Self> {
+    pub fn parse(lines: &mut impl Iterator<Item = Result<(usize, String)>>) -> Result<Self> {
         let seed_to_soil = Self::parse_map(lines).context("Unable to parse seed to soil map")?;
-        lines.next();
         let soil_to_fertilizer =
             Self::parse_map(lines).context("Unable to parse soil to fertilizer map")?;
-        lines.next();
         let fertilizer_to_water =
             Self::parse_map(lines).context("Unable to parse fertilizer to water map")?;
-        lines.next();
         let water_to_light =
             Self::parse_map(lines).context("Unable to parse water to light map")?;
-        lines.next();
         let light_to_temperature =
             Self::parse_map(lines).context("Unable to parse light to temperature map")?;
-        lines.next();
         let temperature_to_humidity =
             Self::parse_map(lines).context("Unable to parse temperature to humidity map")?;
-        lines.next();
         let humidity_to_location =
             Self::parse_map(lines).context("Unable to parse humidity to location map")?;
 
@@ -97,35 +111,36 @@ impl Almanac {
         })
     }
 
-    fn parse_map(lines: &mut impl Iterator<Item = Result<String>>) -> Result<Map> {
+    pub fn parse_map(lines: &mut impl Iterator<Item = Result<(usize, String)>>) -> Result<Map> {
         lines.next();
         let ranges = lines
-            .take_while(|line| !line.as_ref().map(String::is_empty).unwrap_or_default())
+            .take_while(|line| !line.as_ref().is_ok_and(|(_, line)| line.is_empty()))
             .map(|line| {
-                let line = line?;
-                let mut split = line.split(' ');
-                let destination_start = split
-                    .next()
-                    .context("Unable to read destination start")?
-                    .parse()?;
-                let source_start = split
-                    .next()
-                    .context("Unable to read source start")?
-                    .parse()?;
-                let length = split.next().context("Unable to read length")?.parse()?;
-
-                Ok(MapRange {
-                    destination_start,
-                    source_start,
-                    length,
-                })
+                let (line_number, line) = line?;
+                Self::parse_range(&line).map_err(|error| error.on_line(line_number).into())
             })
             .collect::<Result<Vec<_>>>()?;
 
         Ok(Map { ranges })
     }
 
-    fn location_from_seed(&self, seed: u64) -> u64 {
+    pub fn parse_range(line: &str) -> Result<MapRange, ParseError> {
+        let mut split = line.split(' ');
+        let mut next = |what: &str| {
+            let number = split
+                .next()
+                .ok_or_else(|| ParseError::at_end(line, format!("Unable to read {what}")))?;
+            parse_at(line, number, what)
+        };
+
+        Ok(MapRange {
+            destination_start: next("destination start")?,
+            source_start: next("source start")?,
+            length: next("length")?,
+        })
+    }
+
+    pub fn location_from_seed(&self, seed: u64) -> u64 {
         let soil = self.seed_to_soil.map(seed);
         let fertilizer = self.soil_to_fertilizer.map(soil);
         let water = self.fertilizer_to_water.map(fertilizer);
@@ -138,10 +153,18 @@ impl Almanac {
 }
 
 #[derive(Default)]
-pub struct Day5;
+pub struct Day5 {
+    trace: Trace,
+}
 
 impl Solutions for Day5 {
-    fn part1(&mut self, input: Input) -> Result<String> {
+    type Parsed = (Seeds, Almanac);
+
+    fn parse(&mut self, input: Input<'_>) -> Result<Self::Parsed> {
+        Self::parse_input(input)
+    }
+
+    fn part1(&mut self, (seeds, almanac): &Self::Parsed) -> Result<Answer> {
         // Part 1 is incorrect, I think. Let me explain:
         // I had completed part 1 and submitted the correct answer, and thought I was done with it.
         // Later, I was struggling to get part 2 to give me the correct result. Upon rereading the
@@ -151,37 +174,105 @@ impl Solutions for Day5 {
         // answer, and I find that odd. It might just be some other piece of information I have
         // missed or misinterpreted, or some off by one error, but at this point I'm not going to
         // spend any more energy investigating it. Day 5 is done.
+        //
+        // Later found: parsing skipped the first range of every map after the first one, since
+        // the blank line between maps was skipped twice. With that fixed, the example gives the
+        // right answer for both parts.
 
-        let (seeds, almanac) = Self::parse_input(input)?;
+        let traced_seeds = seeds.seeds.iter().copied().inspect(|&seed| {
+            self.trace.emit(|| {
+                Event::new("seed")
+                    .field("seed", seed)
+                    .field("location", almanac.location_from_seed(seed))
+            });
+        });
         let solution =
-            Self::find_lowest_location(seeds.seeds, &almanac).context("No location found")?;
+            Self::find_lowest_location(traced_seeds, almanac).context("No location found")?;
 
-        Ok(solution.to_string())
+        Ok(solution.into())
     }
 
-    fn part2(&mut self, input: Input) -> Result<String> {
-        let (seeds, almanac) = Self::parse_input(input)?;
-        let solution = Self::find_lowest_location(seeds.expand_ranges(), &almanac)
+    fn part2(&mut self, (seeds, almanac): &Self::Parsed) -> Result<Answer> {
+        let solution = Self::find_lowest_location(seeds.expand_ranges(), almanac)
             .context("No location found")?;
 
-        Ok(solution.to_string())
+        Ok(solution.into())
+    }
+
+    fn set_trace(&mut self, trace: Trace) {
+        self.trace = trace;
+    }
+
+    /// `seeds` seed ranges of up to `length` seeds, and the seven maps, each with `ranges`
+    /// ranges that don't overlap, neither in their sources nor in their destinations. All numbers
+    /// are below `max`. Knobs: `seeds`, `length`, `ranges` and `max`.
+    fn generate(&self, rng: &mut Rng, knobs: &mut Knobs) -> Option<String> {
+        let seeds = knobs.get("seeds", 10);
+        let length = knobs.get("length", 100_000).max(1);
+        let ranges = knobs.get("ranges", 30) as usize;
+        let max = knobs.get("max", 4_000_000_000).max(length + 1);
+
+        let seeds = (0..seeds)
+            .map(|_| {
+                let length = rng.range(1..=length);
+                format!("{} {length}", rng.range(0..=max - length))
+            })
+            .collect::<Vec<_>>();
+        let mut input = format!("seeds: {}\n", seeds.join(" "));
+
+        for name in MAP_NAMES {
+            input.push_str(&format!("\n{name} map:\n"));
+
+            // Cut the sources out of the numbers up to max, then lay them out again in another
+            // order with gaps of other sizes between them to get the destinations
+            let cuts = sorted_points(rng, ranges * 2, max);
+            let mut sources = cuts
+                .chunks(2)
+                .filter(|cut| cut[0] < cut[1])
+                .map(|cut| (cut[0], cut[1] - cut[0]))
+                .collect::<Vec<_>>();
+            rng.shuffle(&mut sources);
+            let spare = max - sources.iter().map(|(_, length)| length).sum::<u64>();
+            let gaps = sorted_points(rng, sources.len(), spare);
+
+            let mut destination_start = 0;
+            let mut previous_gap = 0;
+            for ((source_start, length), gap) in sources.into_iter().zip(gaps) {
+                destination_start += gap - previous_gap;
+                previous_gap = gap;
+                input.push_str(&format!("{destination_start} {source_start} {length}\n"));
+                destination_start += length;
+            }
+        }
+
+        Some(input)
     }
 }
 
+/// `count` random numbers up to `max`, in ascending order.
+fn sorted_points(rng: &mut Rng, count: usize, max: u64) -> Vec<u64> {
+    let mut points = (0..count).map(|_| rng.range(0..=max)).collect::<Vec<_>>();
+    points.sort_unstable();
+    points
+}
+
 impl Day5 {
-    fn parse_input(input: Input) -> Result<(Seeds, Almanac)> {
-        let mut lines = input.lines().map(|line| Ok(line?));
-        let seeds: Seeds = lines
-            .next()
-            .context("Unable to read seeds line")??
-            .parse()?;
+    pub fn parse_input(input: Input<'_>) -> Result<(Seeds, Almanac)> {
+        let mut lines = input
+            .lines()
+            .enumerate()
+            .map(|(index, line)| Ok((index + 1, line?)));
+        let (line_number, seeds_line) = lines.next().context("Unable to read seeds line")??;
+        let seeds: Seeds = seeds_line
+            .parse()
+            .map_err(|error: ParseError| error.on_line(line_number))?;
         lines.next();
         let almanac = Almanac::parse(&mut lines)?;
 
         Ok((seeds, almanac))
     }
 
-    fn find_lowest_location(
+    pub fn find_lowest_location(
         seeds: impl IntoIterator<Item = u64>,
         almanac: &Almanac,
     ) -> Option<u64> {
@@ -191,3 +282,56 @@ impl Day5 {
             .min()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "\
+seeds: 79 14 55 13
+
+seed-to-soil map:
+50 98 2
+52 50 48
+
+soil-to-fertilizer map:
+0 15 37
+37 52 2
+39 0 15
+
+fertilizer-to-water map:
+49 53 8
+0 11 42
+42 0 7
+57 7 4
+
+water-to-light map:
+88 18 7
+18 25 70
+
+light-to-temperature map:
+45 77 23
+81 45 19
+68 64 13
+
+temperature-to-humidity map:
+0 69 1
+1 0 69
+
+humidity-to-location map:
+60 56 37
+56 93 4
+";
+
+    #[test]
+    fn part1_example() {
+        let parsed = Day5::default().parse(EXAMPLE.into()).unwrap();
+        assert_eq!(Day5::default().part1(&parsed).unwrap(), Answer::from(35));
+    }
+
+    #[test]
+    fn part2_example() {
+        let parsed = Day5::default().parse(EXAMPLE.into()).unwrap();
+        assert_eq!(Day5::default().part2(&parsed).unwrap(), Answer::from(46));
+    }
+}
@@ -1,26 +1,35 @@
 use std::io::BufRead;
 
-use anyhow::{Context, Error, Result};
+use anyhow::{anyhow, bail, Context, Result};
 
-use crate::{Input, Solutions};
+use crate::{
+    generate::{Knobs, Rng},
+    parse::{parse_at, ParseError},
+    Answer, Input, Solutions,
+};
 
 #[derive(Debug)]
-struct Race {
-    time_ms: u64,
-    record_distance_mm: u64,
+pub struct Race {
+    pub time_ms: u64,
+    pub record_distance_mm: u64,
 }
 
-fn parse_races(input: Input) -> Result<Vec<Race>> {
+/// Read the times and distances lines of the input.
+pub fn read_lines(input: Input<'_>) -> Result<(String, String)> {
     let mut lines = input.lines();
     let times_line = lines.next().context("Unable to read times")??;
     let distances_line = lines.next().context("Unable to read distances")??;
 
-    let times = words(&times_line)
+    Ok((times_line, distances_line))
+}
+
+pub fn parse_races(times_line: &str, distances_line: &str) -> Result<Vec<Race>, ParseError> {
+    let times = words(times_line)
         .skip(1)
-        .map(|s| Ok::<_, Error>(s.parse()?));
-    let distances = words(&distances_line)
+        .map(|s| parse_at(times_line, s, "time").map_err(|error| error.on_line(1)));
+    let distances = words(distances_line)
         .skip(1)
-        .map(|s| Ok::<_, Error>(s.parse()?));
+        .map(|s| parse_at(distances_line, s, "distance").map_err(|error| error.on_line(2)));
 
     times
         .zip(distances)
@@ -33,13 +42,10 @@ fn parse_races(input: Input) -> Result<Vec<Race>> {
         .collect()
 }
 
-fn parse_the_one_true_race(input: Input) -> Result<Race> {
-    let mut lines = input.lines();
-    let time_line = lines.next().context("Unable to read times")??;
-    let distance_line = lines.next().context("Unable to read distances")??;
-
-    let time: u64 = words(&time_line).skip(1).collect::<String>().parse()?;
-    let record_distance = words(&distance_line).skip(1).collect::<String>().parse()?;
+pub fn parse_the_one_true_race(time_line: &str, distance_line: &str) -> Result<Race, ParseError> {
+    let time = parse_kerned(time_line, "time").map_err(|error| error.on_line(1))?;
+    let record_distance =
+        parse_kerned(distance_line, "distance").map_err(|error| error.on_line(2))?;
 
     Ok(Race {
         time_ms: time,
@@ -51,40 +57,214 @@ fn parse_the_one_true_race(input: Input) -> Result<Race> {
 /// a distance of at least `min_distance_mm` in a race that lasts `race_time_ms`.
 ///
 /// The function of (race_time, hold_time) to distance is a quadratic polynomial, so this
-/// implementation uses the quadratic formula to find the hold times given a distance.
-fn hold_time_ms_bounds(race_time_ms: u64, min_distance_mm: u64) -> (u64, u64) {
-    let sqrt_part = ((race_time_ms * race_time_ms - 4 * min_distance_mm) as f64).sqrt();
-
-    (
-        ((race_time_ms as f64 - sqrt_part) / 2.0).ceil() as _,
-        ((race_time_ms as f64 + sqrt_part) / 2.0) as _,
-    )
+/// implementation uses the quadratic formula to find the hold times given a distance. Fails if no
+/// hold time reaches the distance, or if the race is too long to square its time.
+pub fn hold_time_ms_bounds(race_time_ms: u64, min_distance_mm: u64) -> Result<(u64, u64)> {
+    let unreachable =
+        || anyhow!("No hold time reaches {min_distance_mm} mm in a race of {race_time_ms} ms");
+    let squared_time = race_time_ms
+        .checked_mul(race_time_ms)
+        .with_context(|| format!("The race of {race_time_ms} ms is too long to solve"))?;
+    let discriminant = min_distance_mm
+        .checked_mul(4)
+        .and_then(|four_distances| squared_time.checked_sub(four_distances))
+        .ok_or_else(unreachable)?;
+    let sqrt_part = (discriminant as f64).sqrt();
+
+    let lower = ((race_time_ms as f64 - sqrt_part) / 2.0).ceil() as u64;
+    let upper = ((race_time_ms as f64 + sqrt_part) / 2.0) as u64;
+    if lower > upper {
+        return Err(unreachable());
+    }
+
+    Ok((lower, upper))
+}
+
+/// The number of hold times that beat the record of `race`. Fails if there are none, since the
+/// puzzle promises there are.
+pub fn count_ways(race: &Race) -> Result<u64> {
+    // Nothing beats a record of u64::MAX, so saturating leaves it unbeatable
+    let (lower, upper) =
+        hold_time_ms_bounds(race.time_ms, race.record_distance_mm.saturating_add(1))?;
+
+    Ok(upper - lower + 1)
+}
+
+/// The number of hold times that beat the record of `race`, by trying every one of them. Fails if
+/// there are none, since the puzzle promises there are.
+pub fn count_ways_by_enumeration(race: &Race) -> Result<u64> {
+    let ways = (0..=race.time_ms)
+        .filter(|hold_time_ms| {
+            hold_time_ms * (race.time_ms - hold_time_ms) > race.record_distance_mm
+        })
+        .count() as u64;
+    if ways == 0 {
+        bail!(
+            "The record of {} mm in a race of {} ms can't be beaten",
+            race.record_distance_mm,
+            race.time_ms
+        );
+    }
+
+    Ok(ways)
 }
 
 fn words(s: &str) -> impl Iterator<Item = &str> {
     s.split(' ').filter(|s| !s.is_empty())
 }
 
+/// Parse the numbers after the label of `line` as one number, ignoring the spaces between them.
+fn parse_kerned(line: &str, what: &str) -> Result<u64, ParseError> {
+    let numbers = line.split_once(':').map_or(line, |(_, numbers)| numbers);
+    numbers
+        .chars()
+        .filter(|c| *c != ' ')
+        .collect::<String>()
+        .parse()
+        .map_err(|error| {
+            ParseError::at(
+                line,
+                numbers.trim(),
+                format!("Unable to parse {what}: {error}"),
+            )
+        })
+}
+
 #[derive(Default)]
 pub struct Day6;
 
 impl Solutions for Day6 {
-    fn part1(&mut self, input: Input) -> Result<String> {
-        let races = parse_races(input)?;
-        let solution: u64 = races
-            .into_iter()
-            .map(|race| hold_time_ms_bounds(race.time_ms, race.record_distance_mm + 1))
-            .map(|(lower, upper)| upper - lower + 1)
-            .product();
-
-        Ok(solution.to_string())
+    /// The races of part 1 and the one race of part 2, read from the same lines. Each is kept
+    /// whether it parsed or not, so one failing to parse doesn't get in the way of the other part.
+    type Parsed = (Result<Vec<Race>, ParseError>, Result<Race, ParseError>);
+
+    fn parse(&mut self, input: Input<'_>) -> Result<Self::Parsed> {
+        let (times_line, distances_line) = read_lines(input)?;
+
+        Ok((
+            parse_races(&times_line, &distances_line),
+            parse_the_one_true_race(&times_line, &distances_line),
+        ))
     }
 
-    fn part2(&mut self, input: Input) -> Result<String> {
-        let race = parse_the_one_true_race(input)?;
-        let (lower, upper) = hold_time_ms_bounds(race.time_ms, race.record_distance_mm + 1);
-        let solution = upper - lower + 1;
+    fn part1(&mut self, (races, _): &Self::Parsed) -> Result<Answer> {
+        let races = races.as_ref().map_err(Clone::clone)?;
+        // The product of a few races can be too large for a u64
+        let solution: u128 = races
+            .iter()
+            .map(|race| count_ways(race).map(u128::from))
+            .product::<Result<_>>()?;
+
+        Ok(solution.into())
+    }
+
+    fn part2(&mut self, (_, race): &Self::Parsed) -> Result<Answer> {
+        let race = race.as_ref().map_err(Clone::clone)?;
+        let solution = count_ways(race)?;
+
+        Ok(solution.into())
+    }
+
+    /// `races` races lasting up to `time` ms, each with a record that can be beaten, also when
+    /// read as the one race of part 2. Some records are exactly what some hold time reaches.
+    /// There are only as many races as fit in one race of part 2 whose time can be squared.
+    /// Knobs: `races` and `time`.
+    fn generate(&self, rng: &mut Rng, knobs: &mut Knobs) -> Option<String> {
+        let time = knobs.get("time", 99).clamp(2, 999_999_999);
+        let races = knobs
+            .get("races", 4)
+            .clamp(1, 9 / time.to_string().len() as u64);
+
+        // The records are beatable on their own, but not necessarily once kerned, so retry until
+        // they are
+        loop {
+            let (times, distances): (Vec<_>, Vec<_>) = (0..races)
+                .map(|_| {
+                    let time = rng.range(2..=time);
+                    let best = (time / 2) * (time - time / 2);
+                    let record = if rng.chance(0.5) {
+                        let hold = rng.range(1..=time / 2);
+                        (hold * (time - hold)).min(best - 1)
+                    } else {
+                        rng.range(0..=best - 1)
+                    };
+                    (time.to_string(), record.to_string())
+                })
+                .unzip();
+
+            let time = times.concat().parse::<u64>().ok()?;
+            let record = distances.concat().parse::<u64>().ok()?;
+            if record < (time / 2) * (time - time / 2) {
+                let width = times.iter().chain(&distances).map(String::len).max()?;
+                let line = |numbers: &[String]| {
+                    numbers
+                        .iter()
+                        .map(|number| format!("{number:>width$}"))
+                        .collect::<Vec<_>>()
+                        .join("  ")
+                };
+
+                return Some(format!(
+                    "Time:     {}\nDistance: {}\n",
+                    line(&times),
+                    line(&distances)
+                ));
+            }
+        }
+    }
+
+    /// Count the ways to win by enumerating every hold time instead of solving the quadratic.
+    fn oracle(&mut self, part: u8, (races, race): &Self::Parsed) -> Option<Result<Answer>> {
+        let solution = match part {
+            1 => races
+                .as_ref()
+                .map_err(|error| error.clone().into())
+                .and_then(|races| {
+                    races
+                        .iter()
+                        .map(|race| count_ways_by_enumeration(race).map(u128::from))
+                        .product::<Result<u128>>()
+                        .map(Answer::from)
+                }),
+            2 => race
+                .as_ref()
+                .map_err(|error| error.clone().into())
+                .and_then(count_ways_by_enumeration)
+                .map(Answer::from),
+            _ => Err(anyhow!("Invalid part {part}")),
+        };
+
+        Some(solution)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "\
+Time:      7  15   30
+Distance:  9  40  200
+";
+
+    #[test]
+    fn part1_example() {
+        let parsed = Day6.parse(EXAMPLE.into()).unwrap();
+        assert_eq!(Day6.part1(&parsed).unwrap(), Answer::from(288));
+    }
+
+    #[test]
+    fn part2_example() {
+        let parsed = Day6.parse(EXAMPLE.into()).unwrap();
+        assert_eq!(Day6.part2(&parsed).unwrap(), Answer::from(71503));
+    }
 
-        Ok(solution.to_string())
+    #[test]
+    fn unbeatable_record() {
+        let parsed = Day6
+            .parse("Time:      7  15   30\nDistance:  9  40  300\n".into())
+            .unwrap();
+        assert!(Day6.part1(&parsed).is_err());
+        assert!(hold_time_ms_bounds(u64::MAX, 1).is_err());
     }
 }
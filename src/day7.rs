@@ -1,21 +1,26 @@
-use std::{cmp::Ordering, io::BufRead, str::FromStr};
+use std::{cmp::Ordering, collections::HashSet, io::BufRead, str::FromStr};
 
-use anyhow::{bail, Context, Error, Result};
+use anyhow::{anyhow, bail, Error, Result};
 
-use crate::{Input, Solutions};
+use crate::{
+    generate::{Knobs, Rng},
+    parse::{parse_at, ParseError},
+    Answer, Input, Solutions,
+};
 
-struct Round {
-    hand: Hand,
-    bid: u32,
+#[derive(Clone)]
+pub struct Round {
+    pub hand: Hand,
+    pub bid: u32,
 }
 
 #[derive(Clone, Copy, PartialEq, Eq)]
-struct Hand {
-    cards: [Card; 5],
+pub struct Hand {
+    pub cards: [Card; 5],
 }
 
 impl Hand {
-    fn hand_type(&self) -> HandType {
+    pub fn hand_type(&self) -> HandType {
         let cards = self.cards.iter().filter(|card| card.0 != 0);
 
         let mut jokers = 5;
@@ -48,24 +53,38 @@ impl Hand {
 }
 
 impl FromStr for Round {
-    type Err = Error;
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let mut split = s.split(' ');
-        let hand = split.next().context("Unable to read hand")?.parse()?;
-        let bid = split.next().context("Unable to read bid")?.parse()?;
+        let hand_str = split.next().unwrap_or_default();
+        let hand = hand_str
+            .parse::<Hand>()
+            .map_err(|error| error.within(s, hand_str))?;
+        let bid_str = split
+            .next()
+            .ok_or_else(|| ParseError::at_end(s, "Unable to read bid"))?;
+        let bid = parse_at(s, bid_str, "bid")?;
 
         Ok(Self { hand, bid })
     }
 }
 
 impl FromStr for Hand {
-    type Err = Error;
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let mut cards = [Card(0); 5];
-        for (i, card_result) in s.chars().map(Card::try_from).enumerate() {
-            cards[i] = card_result?;
+        let mut chars = s.char_indices();
+        for card in &mut cards {
+            let (i, c) = chars
+                .next()
+                .ok_or_else(|| ParseError::at(s, s, "Expected a hand of 5 cards"))?;
+            *card = Card::try_from(c)
+                .map_err(|error| ParseError::at(s, &s[i..i + c.len_utf8()], error.to_string()))?;
+        }
+        if chars.next().is_some() {
+            return Err(ParseError::at(s, s, "Expected a hand of 5 cards"));
         }
 
         Ok(Self { cards })
@@ -97,7 +116,7 @@ impl PartialOrd for Hand {
 }
 
 #[derive(Debug, PartialEq, PartialOrd, Eq, Ord)]
-enum HandType {
+pub enum HandType {
     HighCard,
     OnePair,
     TwoPairs,
@@ -108,7 +127,7 @@ enum HandType {
 }
 
 impl HandType {
-    fn joke(self, jokers: u32) -> Self {
+    pub fn joke(self, jokers: u32) -> Self {
         match (&self, jokers) {
             (HandType::HighCard, 1) => HandType::OnePair,
             (HandType::HighCard, 2) => HandType::ThreeOfAKind,
@@ -127,8 +146,29 @@ impl HandType {
     }
 }
 
+impl HandType {
+    /// The type of a hand without jokers, by counting how often each card occurs.
+    pub fn of_plain_cards(cards: &[Card]) -> Self {
+        let mut counts = [0u8; 15];
+        for card in cards {
+            counts[card.0 as usize] += 1;
+        }
+        counts.sort_unstable_by(|a, b| b.cmp(a));
+
+        match counts {
+            [5, ..] => HandType::FiveOfAKind,
+            [4, ..] => HandType::FourOfAKind,
+            [3, 2, ..] => HandType::FullHouse,
+            [3, ..] => HandType::ThreeOfAKind,
+            [2, 2, ..] => HandType::TwoPairs,
+            [2, ..] => HandType::OnePair,
+            _ => HandType::HighCard,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Eq, Ord)]
-struct Card(u32);
+pub struct Card(pub u32);
 
 impl TryFrom<char> for Card {
     type Error = Error;
@@ -152,43 +192,159 @@ impl TryFrom<char> for Card {
 pub struct Day7;
 
 impl Solutions for Day7 {
-    fn part1(&mut self, input: Input) -> Result<String> {
-        let mut rounds = input
+    type Parsed = Vec<Round>;
+
+    fn parse(&mut self, input: Input<'_>) -> Result<Self::Parsed> {
+        input
             .lines()
-            .map(|line| line?.parse::<Round>())
-            .collect::<Result<Vec<_>>>()?;
+            .enumerate()
+            .map(|(index, line)| {
+                Ok(line?
+                    .parse()
+                    .map_err(|error: ParseError| error.on_line(index + 1))?)
+            })
+            .collect()
+    }
+
+    fn part1(&mut self, rounds: &Self::Parsed) -> Result<Answer> {
+        let mut rounds = rounds.clone();
         rounds.sort_by_key(|round| round.hand);
 
-        let solution: u32 = rounds
+        let solution: u64 = rounds
             .into_iter()
             .enumerate()
-            .map(|(index, round)| (index + 1) as u32 * round.bid)
+            .map(|(index, round)| (index + 1) as u64 * u64::from(round.bid))
             .sum();
 
-        Ok(solution.to_string())
+        Ok(solution.into())
     }
 
-    fn part2(&mut self, input: Input) -> Result<String> {
-        let mut rounds = input
-            .lines()
-            .map(|line| {
-                let mut round = line?.parse::<Round>()?;
+    fn part2(&mut self, rounds: &Self::Parsed) -> Result<Answer> {
+        let mut rounds = rounds
+            .iter()
+            .map(|round| Round {
                 // Using 0 to mean joker, why not
-                round.hand.cards = round
-                    .hand
-                    .cards
-                    .map(|card| if card.0 == 11 { Card(0) } else { card });
-                Ok(round)
+                hand: Hand {
+                    cards: round
+                        .hand
+                        .cards
+                        .map(|card| if card.0 == 11 { Card(0) } else { card }),
+                },
+                bid: round.bid,
             })
-            .collect::<Result<Vec<_>>>()?;
+            .collect::<Vec<_>>();
         rounds.sort_by_key(|round| round.hand);
 
-        let solution: u32 = rounds
+        let solution: u64 = rounds
             .into_iter()
             .enumerate()
-            .map(|(index, round)| (index + 1) as u32 * round.bid)
+            .map(|(index, round)| (index + 1) as u64 * u64::from(round.bid))
+            .sum();
+
+        Ok(solution.into())
+    }
+
+    /// Rank the hands by counting cards instead of using [`Hand::hand_type`], and in part 2 by
+    /// trying every card for every joker and keeping the best type, instead of using
+    /// [`HandType::joke`].
+    fn oracle(&mut self, part: u8, rounds: &Self::Parsed) -> Option<Result<Answer>> {
+        const JACK: u32 = 11;
+
+        if !matches!(part, 1 | 2) {
+            return Some(Err(anyhow!("Invalid part {part}")));
+        }
+
+        let mut ranked = rounds
+            .iter()
+            .map(|round| {
+                let cards = round.hand.cards;
+                if part == 1 {
+                    return (HandType::of_plain_cards(&cards), cards, round.bid);
+                }
+
+                let substitutes = (2..=14)
+                    .filter(|&value| value != JACK)
+                    .map(Card)
+                    .collect::<Vec<_>>();
+                let jokers = (0..5).filter(|&i| cards[i].0 == JACK).collect::<Vec<_>>();
+                // Every combination of substitutes, counting in base 12 with a digit per joker
+                let best_type = (0..substitutes.len().pow(jokers.len() as u32))
+                    .map(|mut combination| {
+                        let mut substituted = cards;
+                        for &joker in &jokers {
+                            substituted[joker] = substitutes[combination % substitutes.len()];
+                            combination /= substitutes.len();
+                        }
+                        HandType::of_plain_cards(&substituted)
+                    })
+                    .max()
+                    .unwrap_or(HandType::HighCard);
+                // Jokers are the weakest card when breaking ties
+                let cards = cards.map(|card| if card.0 == JACK { Card(1) } else { card });
+                (best_type, cards, round.bid)
+            })
+            .collect::<Vec<_>>();
+        ranked.sort_by(|a, b| a.0.cmp(&b.0).then(a.1.cmp(&b.1)));
+
+        let solution: u64 = ranked
+            .iter()
+            .enumerate()
+            .map(|(index, (_, _, bid))| (index as u64 + 1) * u64::from(*bid))
             .sum();
 
-        Ok(solution.to_string())
+        Some(Ok(solution.into()))
+    }
+
+    /// `hands` distinct hands with bids up to `bid`. Each hand is drawn from a few card values, so
+    /// every hand type comes up, with and without jokers. Knobs: `hands` and `bid`.
+    fn generate(&self, rng: &mut Rng, knobs: &mut Knobs) -> Option<String> {
+        const CARDS: &[u8] = b"23456789TJQKA";
+
+        // There are only so many distinct hands
+        let hands = knobs.get("hands", 1000).min(13u64.pow(5)) as usize;
+        let bid = knobs.get("bid", 1000).max(1);
+
+        let mut seen = HashSet::new();
+        let mut input = String::new();
+        while seen.len() < hands {
+            let mut values = CARDS.to_vec();
+            rng.shuffle(&mut values);
+            let values = &values[..rng.range(1..=5) as usize];
+            let hand = (0..5).map(|_| *rng.choose(values)).collect::<Vec<_>>();
+            if seen.insert(hand.clone()) {
+                input.push_str(&format!(
+                    "{} {}\n",
+                    String::from_utf8(hand).ok()?,
+                    rng.range(1..=bid)
+                ));
+            }
+        }
+
+        Some(input)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "\
+32T3K 765
+T55J5 684
+KK677 28
+KTJJT 220
+QQQJA 483
+";
+
+    #[test]
+    fn part1_example() {
+        let parsed = Day7.parse(EXAMPLE.into()).unwrap();
+        assert_eq!(Day7.part1(&parsed).unwrap(), Answer::from(6440));
+    }
+
+    #[test]
+    fn part2_example() {
+        let parsed = Day7.parse(EXAMPLE.into()).unwrap();
+        assert_eq!(Day7.part2(&parsed).unwrap(), Answer::from(5905));
     }
 }
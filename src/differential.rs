@@ -0,0 +1,365 @@
+use std::{ops::Range, thread, time::Duration};
+
+use anyhow::{Error, Result};
+
+use crate::{
+    generate::{self, Knobs},
+    isolated,
+    report::{find_cause, Abort},
+    Answer, Day,
+};
+
+/// The most candidates tried while minimizing a failing input, so minimizing an input on which
+/// the oracle is slow still ends.
+const MAX_ATTEMPTS: usize = 1000;
+
+/// The prefix of the names of the threads that inputs are parsed and solved on while checking,
+/// see [`is_worker_thread`].
+const WORKER_PREFIX: &str = "fuzz ";
+
+/// How checking a part against its oracle on generated inputs went.
+pub enum Outcome {
+    /// The part has no oracle to check it against.
+    NoOracle,
+    /// The part agreed with its oracle on all of this many inputs.
+    Agreed(u64),
+    Diverged(Divergence),
+}
+
+/// An input on which a part disagrees with its oracle.
+pub struct Divergence {
+    /// The seed the input was generated from, before it was minimized.
+    pub seed: u64,
+    /// The input minimized to as few lines, columns and as small numbers as still diverge, unless
+    /// the part timed out on it.
+    pub input: String,
+    pub minimized: bool,
+    pub answer: Result<Answer>,
+    /// What the oracle says the answer is.
+    pub expected: Answer,
+}
+
+enum Comparison {
+    Agreed,
+    Diverged {
+        answer: Result<Answer>,
+        expected: Answer,
+    },
+    NoOracle,
+    /// The input doesn't parse, or the oracle rejected it, so it says nothing about the part.
+    Rejected(Error),
+}
+
+/// Whether the current thread is one that [`check`] parses or solves an input on. Panics on those
+/// threads are reported as answers, so a panic hook can use this to keep them quiet.
+pub fn is_worker_thread() -> bool {
+    thread::current()
+        .name()
+        .is_some_and(|name| name.starts_with(WORKER_PREFIX))
+}
+
+/// Check `part` of `day` against its oracle on the inputs generated from `seeds`, and minimize
+/// the first input they diverge on.
+///
+/// Minimizing tries many inputs, so an input the part times out on isn't minimized, and
+/// minimizing stops at the first candidate that times out.
+pub fn check(
+    day: &Day,
+    part: u8,
+    seeds: Range<u64>,
+    knobs: &Knobs,
+    timeout: Option<Duration>,
+) -> Result<Outcome> {
+    let cases = seeds.end.saturating_sub(seeds.start);
+    for seed in seeds {
+        let input = generate::generate(day, seed, knobs)?;
+        match compare(day, part, &input, timeout) {
+            Comparison::Agreed => {}
+            Comparison::NoOracle => return Ok(Outcome::NoOracle),
+            Comparison::Rejected(error) => {
+                return Err(error.context(format!(
+                    "The input generated from seed {seed} is invalid for day {}",
+                    day.day
+                )))
+            }
+            Comparison::Diverged { answer, expected } => {
+                let original = Divergence {
+                    seed,
+                    input,
+                    minimized: false,
+                    answer,
+                    expected,
+                };
+                if original.answer.as_ref().is_err_and(timed_out) {
+                    return Ok(Outcome::Diverged(original));
+                }
+
+                let mut gave_up = false;
+                let minimized = minimize(&original.input, |candidate| {
+                    if gave_up {
+                        return false;
+                    }
+                    match compare(day, part, candidate, timeout) {
+                        Comparison::Diverged { answer, .. } => {
+                            gave_up = answer.as_ref().is_err_and(timed_out);
+                            !gave_up
+                        }
+                        Comparison::Rejected(error) => {
+                            gave_up = timed_out(&error);
+                            false
+                        }
+                        Comparison::Agreed | Comparison::NoOracle => false,
+                    }
+                });
+                // A part that times out on some inputs may diverge one time but not the next, in
+                // which case the original input has to do
+                let divergence = match compare(day, part, &minimized, timeout) {
+                    Comparison::Diverged { answer, expected } => Divergence {
+                        seed,
+                        input: minimized,
+                        minimized: true,
+                        answer,
+                        expected,
+                    },
+                    _ => original,
+                };
+
+                return Ok(Outcome::Diverged(divergence));
+            }
+        }
+    }
+
+    Ok(Outcome::Agreed(cases))
+}
+
+fn timed_out(error: &Error) -> bool {
+    matches!(find_cause::<Abort>(error), Some(Abort::TimedOut(_)))
+}
+
+/// Parse `input` and solve `part` both ways. Parsing, the part and the oracle each run isolated
+/// as in [`Day::run_part_isolated`], so they can panic or hang, on threads named so
+/// [`is_worker_thread`] recognizes them.
+fn compare(day: &Day, part: u8, input: &str, timeout: Option<Duration>) -> Comparison {
+    let name = |task: &str| format!("{WORKER_PREFIX}day{} {task}", day.day);
+
+    let mut parser = day.fork();
+    let owned_input = input.to_owned();
+    let parsed = match isolated(name("parse"), timeout, move || {
+        parser.solutions.parse(owned_input.as_str().into())
+    })
+    .and_then(|parsed| parsed)
+    {
+        Ok(parsed) => parsed,
+        Err(error) => return Comparison::Rejected(error.context("Unable to parse the input")),
+    };
+
+    let mut oracle = day.fork();
+    let oracle_parsed = parsed.clone();
+    let expected = match isolated(name("oracle"), timeout, move || {
+        oracle.solutions.oracle(part, &oracle_parsed)
+    }) {
+        Ok(None) => return Comparison::NoOracle,
+        Ok(Some(Ok(expected))) => expected,
+        Ok(Some(Err(error))) | Err(error) => {
+            return Comparison::Rejected(error.context("The oracle rejected the input"))
+        }
+    };
+
+    let mut solver = day.fork();
+    let answer = isolated(name(&format!("part{part}")), timeout, move || {
+        solver.run_part(part, &parsed).answer
+    })
+    .and_then(|answer| answer);
+    if answer.as_ref().is_ok_and(|answer| *answer == expected) {
+        Comparison::Agreed
+    } else {
+        Comparison::Diverged { answer, expected }
+    }
+}
+
+/// Shrink `input` for as long as it keeps failing, by removing lines, removing columns of
+/// whitespace-separated words and making numbers smaller, until none of them help anymore.
+fn minimize(input: &str, fails: impl FnMut(&str) -> bool) -> String {
+    let mut minimizer = Minimizer { fails, attempts: 0 };
+    let mut lines = input.lines().map(str::to_owned).collect::<Vec<_>>();
+    loop {
+        let before = lines.clone();
+        minimizer.remove_lines(&mut lines);
+        minimizer.remove_columns(&mut lines);
+        minimizer.shrink_numbers(&mut lines);
+        if lines == before || minimizer.attempts >= MAX_ATTEMPTS {
+            break;
+        }
+    }
+
+    join(&lines)
+}
+
+struct Minimizer<F> {
+    fails: F,
+    attempts: usize,
+}
+
+impl<F: FnMut(&str) -> bool> Minimizer<F> {
+    fn fails(&mut self, lines: &[String]) -> bool {
+        if self.attempts >= MAX_ATTEMPTS {
+            return false;
+        }
+        self.attempts += 1;
+
+        (self.fails)(&join(lines))
+    }
+
+    /// Remove chunks of lines, from half of them down to single lines.
+    fn remove_lines(&mut self, lines: &mut Vec<String>) {
+        let mut chunk = (lines.len() / 2).max(1);
+        loop {
+            let mut start = 0;
+            while start < lines.len() {
+                let end = (start + chunk).min(lines.len());
+                let candidate = [&lines[..start], &lines[end..]].concat();
+                if !candidate.is_empty() && self.fails(&candidate) {
+                    *lines = candidate;
+                } else {
+                    start += chunk;
+                }
+            }
+
+            if chunk == 1 {
+                break;
+            }
+            chunk /= 2;
+        }
+    }
+
+    /// Remove the same word from every line that has it, like a race from both lines of day 6.
+    /// The first word of each line is kept, since it's usually a label.
+    fn remove_columns(&mut self, lines: &mut Vec<String>) {
+        let columns = lines
+            .iter()
+            .map(|line| words(line).len())
+            .max()
+            .unwrap_or(0);
+        for column in (1..columns).rev() {
+            let candidate = lines
+                .iter()
+                .map(|line| {
+                    let mut words = words(line);
+                    if column < words.len() {
+                        words.remove(column);
+                    }
+                    words.join(" ")
+                })
+                .collect::<Vec<_>>();
+            if self.fails(&candidate) {
+                *lines = candidate;
+            }
+        }
+    }
+
+    /// Replace every number with 0, 1, half of it or one less, for as long as one of them fails.
+    fn shrink_numbers(&mut self, lines: &mut [String]) {
+        for index in 0..lines.len() {
+            for column in 0..words(&lines[index]).len() {
+                while let Ok(number) = words(&lines[index])[column].parse::<u64>() {
+                    let smaller = [0, 1, number / 2, number.saturating_sub(1)]
+                        .into_iter()
+                        .filter(|&smaller| smaller < number)
+                        .find_map(|smaller| {
+                            let mut words = words(&lines[index]);
+                            let replacement = smaller.to_string();
+                            words[column] = &replacement;
+                            let mut candidate = lines.to_vec();
+                            candidate[index] = words.join(" ");
+                            self.fails(&candidate).then_some(candidate)
+                        });
+
+                    match smaller {
+                        Some(candidate) => lines.clone_from_slice(&candidate),
+                        None => break,
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn words(line: &str) -> Vec<&str> {
+    line.split_whitespace().collect()
+}
+
+fn join(lines: &[String]) -> String {
+    lines.iter().map(|line| format!("{line}\n")).collect()
+}
+
+/// Print how checking `part` of `day` went, with the minimized input if it diverged.
+pub fn print_outcome(day: usize, part: u8, outcome: &Outcome) {
+    match outcome {
+        Outcome::NoOracle => println!("Day {day} part {part}: no oracle"),
+        Outcome::Agreed(cases) => {
+            println!("Day {day} part {part}: agreed with the oracle on {cases} input(s)")
+        }
+        Outcome::Diverged(divergence) => {
+            let minimized = if divergence.minimized {
+                ", minimized to"
+            } else {
+                ""
+            };
+            println!(
+                "Day {day} part {part}: DIVERGED on the input from seed {}{minimized}:",
+                divergence.seed
+            );
+            for line in divergence.input.lines() {
+                println!("    {line}");
+            }
+            match &divergence.answer {
+                Ok(answer) => println!("Answer:   {answer}"),
+                Err(error) => println!("Answer:   {error:#}"),
+            }
+            println!("Expected: {}", divergence.expected);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn minimize_removes_lines() {
+        let minimized = minimize("a 1\nbad 2\nc 3\nd 4\n", |input| input.contains("bad"));
+        assert_eq!(minimized, "bad\n");
+    }
+
+    #[test]
+    fn minimize_removes_columns() {
+        let minimized = minimize("Time: 7 15 30\nDistance: 9 40 200\n", |input| {
+            let lines = input.lines().map(words).collect::<Vec<_>>();
+            lines.len() == 2 && lines[0].contains(&"15") && lines[1].contains(&"40")
+        });
+        assert_eq!(minimized, "Time: 15\nDistance: 40\n");
+    }
+
+    #[test]
+    fn minimize_shrinks_numbers() {
+        let minimized = minimize("n 1000\n", |input| {
+            words(input.trim())
+                .last()
+                .and_then(|n| n.parse::<u64>().ok())
+                >= Some(37)
+        });
+        assert_eq!(minimized, "n 37\n");
+    }
+
+    #[test]
+    fn minimize_stops_after_max_attempts() {
+        let input = (0..600).map(|line| format!("{line}\n")).collect::<String>();
+        let mut attempts = 0;
+        let minimized = minimize(&input, |candidate| {
+            attempts += 1;
+            candidate.lines().count() == 600
+        });
+        assert_eq!(attempts, MAX_ATTEMPTS);
+        assert_eq!(minimized, input);
+    }
+}
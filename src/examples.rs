@@ -0,0 +1,230 @@
+use std::{
+    fs::{self, ReadDir},
+    path::{Path, PathBuf},
+    slice,
+    time::Duration,
+};
+
+use anyhow::{Context, Result};
+
+use crate::{
+    answers::Answers,
+    bench::format_duration,
+    input::default_path,
+    report::{print_parse_error, DayReport, PartReport},
+    verify::{verify, Verdict, Verification},
+};
+
+const ANSWERS_EXTENSION: &str = "answers";
+
+/// A worked example from the puzzle text, stored next to the real input as e.g.
+/// `puzzle-input/day3.example1`, or one of a directory of inputs found by [`find_inputs`].
+///
+/// Its expected answers are read from a file with the same name plus `.answers`, like
+/// `puzzle-input/day3.example1.answers`, in the same format as the answers file but without the
+/// need for a `[day#]` section:
+///
+/// ```toml
+/// part1 = 4361
+/// part2 = 467835
+/// ```
+pub struct Example {
+    /// The file name of the example, e.g. `day3.example1`.
+    pub name: String,
+    pub path: PathBuf,
+    /// The expected answers, or `None` if the example has no answers file.
+    pub answers: Option<Answers>,
+}
+
+impl Example {
+    /// The parts to run for this example out of `parts`. Examples often only apply to one of the
+    /// parts, so if there are expected answers only the parts that have one are run.
+    pub fn parts(&self, day: usize, parts: &[u8]) -> Vec<u8> {
+        parts
+            .iter()
+            .copied()
+            .filter(|&part| {
+                self.answers
+                    .as_ref()
+                    .is_none_or(|answers| answers.contains(day, part))
+            })
+            .collect()
+    }
+}
+
+/// Find all examples for `day`, ordered by name.
+pub fn find_examples(day: usize) -> Result<Vec<Example>> {
+    let input_path = default_path(day);
+    let directory = input_path.parent().context("Input path has no directory")?;
+    let prefix = format!(
+        "{}.example",
+        input_path
+            .file_name()
+            .context("Input path has no file name")?
+            .to_string_lossy()
+    );
+
+    let entries = match fs::read_dir(directory) {
+        Ok(entries) => entries,
+        Err(_) => return Ok(Vec::new()),
+    };
+
+    collect(entries, day, |name| name.starts_with(&prefix))
+}
+
+/// Find all inputs for `day` in `directory`, ordered by name. Every file in it is an input, and
+/// can have expected answers next to it the same way examples do, e.g. `alice` and
+/// `alice.answers`.
+pub fn find_inputs(directory: &Path, day: usize) -> Result<Vec<Example>> {
+    let entries = fs::read_dir(directory)
+        .with_context(|| format!("Unable to read directory {}", directory.display()))?;
+
+    collect(entries, day, |_| true)
+}
+
+fn collect(entries: ReadDir, day: usize, include: impl Fn(&str) -> bool) -> Result<Vec<Example>> {
+    let mut examples = Vec::new();
+    for entry in entries {
+        let path = entry?.path();
+        let name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        if !path.is_file() || !include(&name) || name.ends_with(&format!(".{ANSWERS_EXTENSION}")) {
+            continue;
+        }
+
+        let answers_path = path.with_file_name(format!("{name}.{ANSWERS_EXTENSION}"));
+        let answers = if answers_path.exists() {
+            let contents = fs::read_to_string(&answers_path)
+                .with_context(|| format!("Unable to read {}", answers_path.display()))?;
+            Some(
+                Answers::parse_for_day(&contents, day)
+                    .with_context(|| format!("Unable to parse {}", answers_path.display()))?,
+            )
+        } else {
+            None
+        };
+
+        examples.push(Example {
+            name,
+            path,
+            answers,
+        });
+    }
+    examples.sort_by(|a, b| a.name.cmp(&b.name));
+
+    Ok(examples)
+}
+
+/// The result of one part on one of a directory of inputs, checked against the input's expected
+/// answers if it has any.
+pub struct InputResult<'a> {
+    pub input: &'a Example,
+    pub parse_elapsed: Duration,
+    pub part: &'a PartReport,
+    pub verification: Verification,
+}
+
+impl InputResult<'_> {
+    /// PASS or FAIL if the input has an expected answer for the part, and otherwise OK. Parts
+    /// that didn't get an answer are ERROR if they failed with an error of their own, like an
+    /// input they can't parse, and PANIC or TIMEOUT if they were aborted.
+    pub fn status(&self) -> &'static str {
+        match (&self.part.answer, self.verification.verdict) {
+            (Err(_), _) => match self.part.status() {
+                "FAIL" => "ERROR",
+                status => status,
+            },
+            (Ok(_), Verdict::Pass) => "PASS",
+            (Ok(_), Verdict::Fail) => "FAIL",
+            (Ok(_), Verdict::Missing) => "OK",
+        }
+    }
+
+    /// Whether the part got the wrong answer, failed with an error, panicked or timed out.
+    pub fn failed(&self) -> bool {
+        !matches!(self.status(), "PASS" | "OK")
+    }
+}
+
+/// Pair every part of `reports` with the input it ran on, `reports` being in the same order as
+/// `inputs`.
+pub fn input_results<'a>(inputs: &'a [Example], reports: &'a [DayReport]) -> Vec<InputResult<'a>> {
+    let no_answers = Answers::default();
+    inputs
+        .iter()
+        .zip(reports)
+        .flat_map(|(input, report)| {
+            let answers = input.answers.as_ref().unwrap_or(&no_answers);
+            let verifications = verify(slice::from_ref(report), answers);
+            report
+                .parts
+                .iter()
+                .zip(verifications)
+                .map(move |(part, verification)| InputResult {
+                    input,
+                    parse_elapsed: report.parse_elapsed,
+                    part,
+                    verification,
+                })
+        })
+        .collect()
+}
+
+/// Print a table of the results of every input and part, followed by the errors of any failed
+/// parts.
+pub fn print_input_results(results: &[InputResult]) {
+    let name_width = results
+        .iter()
+        .map(|result| result.input.name.len())
+        .max()
+        .unwrap_or(0)
+        .max("File".len());
+    let answer_width = results
+        .iter()
+        .filter(|result| result.part.answer.is_ok())
+        .map(|result| result.verification.actual.len())
+        .chain(
+            results
+                .iter()
+                .filter_map(|result| result.verification.expected.as_ref().map(String::len)),
+        )
+        .max()
+        .unwrap_or(0)
+        .max("Expected".len());
+    println!(
+        "{:name_width$}  Part  {:answer_width$}  {:answer_width$}  Status   {:>10}  {:>10}",
+        "File", "Answer", "Expected", "Parse", "Time"
+    );
+
+    let mut errors = Vec::new();
+    for result in results {
+        let answer = match &result.part.answer {
+            Ok(_) => result.verification.actual.as_str(),
+            Err(error) => {
+                errors.push((&result.input.name, result.part.part, error));
+                "-"
+            }
+        };
+        println!(
+            "{:name_width$}  {:>4}  {:answer_width$}  {:answer_width$}  {:7}  {:>10}  {:>10}",
+            result.input.name,
+            result.part.part,
+            answer,
+            result.verification.expected.as_deref().unwrap_or("-"),
+            result.status(),
+            format_duration(result.parse_elapsed),
+            format_duration(result.part.elapsed),
+        );
+    }
+
+    if !errors.is_empty() {
+        println!();
+        println!("Errors:");
+        for (name, part, error) in errors {
+            println!("  {name} part {part}: {error:#}");
+            print_parse_error(error, "    ");
+        }
+    }
+}
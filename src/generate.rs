@@ -0,0 +1,145 @@
+use std::{
+    collections::HashMap,
+    fs,
+    ops::{Range, RangeInclusive},
+    path::Path,
+};
+
+use anyhow::{bail, Context, Result};
+
+use crate::{bench::format_bytes, Day};
+
+/// A small, seedable pseudo-random number generator (SplitMix64), so generated inputs are the same
+/// for the same seed on every machine.
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Self(seed)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// A number in `range`, which must not be empty.
+    pub fn range(&mut self, range: RangeInclusive<u64>) -> u64 {
+        let (start, end) = (*range.start(), *range.end());
+        let span = (end - start) as u128 + 1;
+        start + ((self.next_u64() as u128 * span) >> 64) as u64
+    }
+
+    /// An index below `len`, which must not be 0.
+    pub fn index(&mut self, len: usize) -> usize {
+        self.range(0..=len as u64 - 1) as usize
+    }
+
+    /// Whether an event with the given probability happens.
+    pub fn chance(&mut self, probability: f64) -> bool {
+        ((self.next_u64() >> 11) as f64 / (1u64 << 53) as f64) < probability
+    }
+
+    pub fn choose<'a, T>(&mut self, items: &'a [T]) -> &'a T {
+        &items[self.index(items.len())]
+    }
+
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            items.swap(i, self.index(i + 1));
+        }
+    }
+}
+
+/// Named knobs for the size of a generated input, like the number of hands for day 7, parsed from
+/// e.g. `hands=10000,bid=1000`. Each day reads the knobs that apply to it with [`Knobs::get`],
+/// falling back to a default for the ones that aren't set.
+#[derive(Clone, Default)]
+pub struct Knobs {
+    values: HashMap<String, u64>,
+    read: Vec<&'static str>,
+}
+
+impl Knobs {
+    pub fn parse(s: &str) -> Result<Self> {
+        let values = s
+            .split(',')
+            .filter(|knob| !knob.is_empty())
+            .map(|knob| {
+                let (name, value) = knob
+                    .split_once('=')
+                    .with_context(|| format!("Expected <knob>=<n>, found \"{knob}\""))?;
+                let value = value
+                    .parse()
+                    .with_context(|| format!("Unable to parse the value of knob \"{name}\""))?;
+
+                Ok((name.to_owned(), value))
+            })
+            .collect::<Result<_>>()?;
+
+        Ok(Self {
+            values,
+            read: Vec::new(),
+        })
+    }
+
+    /// The value of the knob called `name`, or `default` if it isn't set.
+    pub fn get(&mut self, name: &'static str, default: u64) -> u64 {
+        self.read.push(name);
+        self.values.get(name).copied().unwrap_or(default)
+    }
+
+    /// Fail if any knob was set that the generator never read, since it doesn't apply to the day.
+    fn check_read(&self, day: usize) -> Result<()> {
+        let mut unread = self
+            .values
+            .keys()
+            .filter(|name| !self.read.contains(&name.as_str()))
+            .collect::<Vec<_>>();
+        unread.sort();
+        if let Some(name) = unread.first() {
+            bail!(
+                "Day {day} has no knob \"{name}\", only {}",
+                self.read.join(", ")
+            );
+        }
+
+        Ok(())
+    }
+}
+
+/// Generate an input for `day` from every seed in `seeds`, and write them to `output` as
+/// `seed-#`, printing the path and size of each.
+pub fn write_inputs(day: &Day, seeds: Range<u64>, knobs: &Knobs, output: &Path) -> Result<()> {
+    fs::create_dir_all(output)
+        .with_context(|| format!("Unable to create directory {}", output.display()))?;
+
+    for seed in seeds {
+        let input = generate(day, seed, knobs)?;
+        let path = output.join(format!("seed-{seed}"));
+        fs::write(&path, &input).with_context(|| format!("Unable to write {}", path.display()))?;
+        println!(
+            "Wrote {} ({} lines, {})",
+            path.display(),
+            input.lines().count(),
+            format_bytes(input.len() as u64)
+        );
+    }
+
+    Ok(())
+}
+
+/// Generate a random input for `day` from `seed`, sized by `knobs`.
+pub fn generate(day: &Day, seed: u64, knobs: &Knobs) -> Result<String> {
+    let mut knobs = knobs.clone();
+    let input = day
+        .solutions
+        .generate(&mut Rng::new(seed), &mut knobs)
+        .with_context(|| format!("Day {} has no input generator", day.day))?;
+    knobs.check_read(day.day)?;
+
+    Ok(input)
+}
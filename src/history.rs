@@ -0,0 +1,474 @@
+use std::{
+    fs::{self, OpenOptions},
+    io::{self, Write},
+    path::Path,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::{bail, Context, Result};
+
+use crate::{bench::format_duration, report::DayReport};
+
+/// Where the history of runs is kept, relative to the root of the repository.
+pub const DEFAULT_PATH: &str = "run-history.tsv";
+
+/// Slowdowns smaller than this are never flagged, since timings that short are mostly noise.
+const MIN_SLOWDOWN: Duration = Duration::from_millis(1);
+
+const HEADER: &str =
+    "# timestamp_ms\tprofile\tday\tpart\tstatus\tanswer\tparse_elapsed_ns\telapsed_ns";
+
+/// The result of one part in one recorded run.
+#[derive(Clone)]
+pub struct Entry {
+    /// When the run happened, in milliseconds since the Unix epoch. All parts of a run share it.
+    pub timestamp: u64,
+    /// `debug` or `release`, since timings of the two can't be compared.
+    pub profile: String,
+    pub day: usize,
+    pub part: u8,
+    /// The status as in [`crate::report::PartReport::status`].
+    pub status: String,
+    /// The answer, or `None` if the part failed.
+    pub answer: Option<String>,
+    pub parse_elapsed: Duration,
+    pub elapsed: Duration,
+}
+
+impl Entry {
+    /// The time it took to get the answer, including parsing.
+    pub fn total_elapsed(&self) -> Duration {
+        self.parse_elapsed + self.elapsed
+    }
+
+    fn parse(line: &str) -> Result<Self> {
+        let fields = line.split('\t').collect::<Vec<_>>();
+        let [timestamp, profile, day, part, status, answer, parse_elapsed, elapsed] = fields[..]
+        else {
+            bail!("Expected 8 tab-separated fields, found {}", fields.len());
+        };
+
+        Ok(Self {
+            timestamp: timestamp.parse().context("Unable to parse timestamp")?,
+            profile: profile.to_owned(),
+            day: day.parse().context("Unable to parse day")?,
+            part: part.parse().context("Unable to parse part")?,
+            status: status.to_owned(),
+            answer: (status == "OK").then(|| answer.to_owned()),
+            parse_elapsed: Duration::from_nanos(
+                parse_elapsed
+                    .parse()
+                    .context("Unable to parse parse time")?,
+            ),
+            elapsed: Duration::from_nanos(elapsed.parse().context("Unable to parse time")?),
+        })
+    }
+}
+
+/// The current build profile, which entries are recorded with.
+pub fn profile() -> &'static str {
+    if cfg!(debug_assertions) {
+        "debug"
+    } else {
+        "release"
+    }
+}
+
+/// Turn the reports of a run into history entries, timestamped now.
+pub fn entries(reports: &[DayReport]) -> Vec<Entry> {
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64;
+
+    reports
+        .iter()
+        .flat_map(|report| {
+            report.parts.iter().map(move |part| Entry {
+                timestamp,
+                profile: profile().to_owned(),
+                day: report.day,
+                part: part.part,
+                status: part.status().to_owned(),
+                // Tabs and newlines would break the format, and answers don't have any anyway
+                answer: part
+                    .answer
+                    .as_ref()
+                    .ok()
+                    .map(|answer| answer.to_string().replace(['\t', '\n'], " ")),
+                parse_elapsed: report.parse_elapsed,
+                elapsed: part.elapsed,
+            })
+        })
+        .collect()
+}
+
+/// Load the history at `path`, oldest first. A missing file is an empty history.
+pub fn load(path: &Path) -> Result<Vec<Entry>> {
+    let contents = match fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(error) => {
+            return Err(error).with_context(|| format!("Unable to read {}", path.display()))
+        }
+    };
+
+    contents
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'))
+        .map(|(index, line)| {
+            Entry::parse(line).with_context(|| {
+                format!("Unable to parse line {} of {}", index + 1, path.display())
+            })
+        })
+        .collect()
+}
+
+/// Append `entries` to the history at `path`, creating it if needed.
+pub fn append(path: &Path, entries: &[Entry]) -> Result<()> {
+    let is_new = !path.exists();
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .with_context(|| format!("Unable to open {}", path.display()))?;
+
+    let mut lines = String::new();
+    if is_new {
+        lines.push_str(HEADER);
+        lines.push('\n');
+    }
+    for entry in entries {
+        lines.push_str(&format!(
+            "{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\n",
+            entry.timestamp,
+            entry.profile,
+            entry.day,
+            entry.part,
+            entry.status,
+            entry.answer.as_deref().unwrap_or_default(),
+            entry.parse_elapsed.as_nanos(),
+            entry.elapsed.as_nanos(),
+        ));
+    }
+
+    file.write_all(lines.as_bytes())
+        .with_context(|| format!("Unable to write {}", path.display()))
+}
+
+/// Something about a part that changed since the last recorded run.
+#[derive(Clone, Copy, PartialEq)]
+pub enum Flag {
+    AnswerChanged,
+    /// The part succeeded last time, but not this time.
+    Failing,
+    /// The part took longer than the threshold allows, by the given factor.
+    Slower(f64),
+}
+
+impl Flag {
+    fn describe(self) -> String {
+        match self {
+            Self::AnswerChanged => "ANSWER CHANGED".to_owned(),
+            Self::Failing => "NOW FAILING".to_owned(),
+            Self::Slower(factor) => format!("SLOWER ({factor:.2}x)"),
+        }
+    }
+}
+
+/// An entry next to the entry of the same day and part in the run before it.
+pub struct Comparison<'a> {
+    pub previous: Option<&'a Entry>,
+    pub current: &'a Entry,
+    pub flags: Vec<Flag>,
+}
+
+impl Comparison<'_> {
+    /// Whether the part's answer changed or it started failing, as opposed to only getting slower.
+    pub fn answer_changed(&self) -> bool {
+        self.flags
+            .iter()
+            .any(|flag| matches!(flag, Flag::AnswerChanged | Flag::Failing))
+    }
+}
+
+/// Compare `current` with `previous`, flagging changed answers and parts that have become slower
+/// by more than `threshold`, e.g. 0.1 for 10%. Slowdowns are only flagged against a run of the
+/// same build profile, and only if they are at least a millisecond.
+pub fn compare(previous: Option<&Entry>, current: &Entry, threshold: f64) -> Vec<Flag> {
+    let Some(previous) = previous else {
+        return Vec::new();
+    };
+
+    let mut flags = Vec::new();
+    match (&previous.answer, &current.answer) {
+        (Some(previous), Some(current)) if previous != current => flags.push(Flag::AnswerChanged),
+        (Some(_), None) => flags.push(Flag::Failing),
+        _ => {}
+    }
+
+    let factor =
+        current.total_elapsed().as_secs_f64() / previous.total_elapsed().as_secs_f64().max(1e-9);
+    let slowdown = current
+        .total_elapsed()
+        .saturating_sub(previous.total_elapsed());
+    if previous.profile == current.profile
+        && current.answer.is_some()
+        && factor > 1.0 + threshold
+        && slowdown >= MIN_SLOWDOWN
+    {
+        flags.push(Flag::Slower(factor));
+    }
+
+    flags
+}
+
+/// Pair every entry of `current` with the most recent entry for the same day and part in
+/// `history`, preferably one of the same build profile.
+pub fn compare_with_history<'a>(
+    history: &'a [Entry],
+    current: &'a [Entry],
+    threshold: f64,
+) -> Vec<Comparison<'a>> {
+    current
+        .iter()
+        .map(|entry| {
+            let same_part =
+                |previous: &&Entry| previous.day == entry.day && previous.part == entry.part;
+            // Prefer a run of the same profile, so slowdowns can be flagged
+            let previous = history
+                .iter()
+                .rev()
+                .filter(same_part)
+                .find(|previous| previous.profile == entry.profile)
+                .or_else(|| history.iter().rev().find(same_part));
+
+            Comparison {
+                previous,
+                current: entry,
+                flags: compare(previous, entry, threshold),
+            }
+        })
+        .collect()
+}
+
+/// Print the comparisons as a table with the previous and current answer and time of each part.
+pub fn print_comparisons(comparisons: &[Comparison]) {
+    let answer_width = comparisons
+        .iter()
+        .flat_map(|comparison| [comparison.previous, Some(comparison.current)])
+        .flatten()
+        .map(|entry| answer(entry).len())
+        .max()
+        .unwrap_or(0)
+        .max("Previous".len());
+
+    println!(
+        "Day  Part  {:answer_width$}  {:answer_width$}  {:>10}  {:>10}  Flags",
+        "Previous", "Answer", "Was", "Time"
+    );
+    for comparison in comparisons {
+        let current = comparison.current;
+        let row = format!(
+            "{:>3}  {:>4}  {:answer_width$}  {:answer_width$}  {:>10}  {:>10}  {}",
+            current.day,
+            current.part,
+            comparison.previous.map_or("-", answer),
+            answer(current),
+            comparison.previous.map_or_else(
+                || "-".to_owned(),
+                |entry| format_duration(entry.total_elapsed())
+            ),
+            format_duration(current.total_elapsed()),
+            describe_flags(&comparison.flags),
+        );
+        println!("{}", row.trim_end());
+    }
+}
+
+/// Print the recorded runs of every day and part in `days` and `parts`, oldest first, with each
+/// run flagged against the one before it.
+pub fn print_history(
+    history: &[Entry],
+    days: &[usize],
+    parts: &[u8],
+    limit: usize,
+    threshold: f64,
+) {
+    for &day in days {
+        for &part in parts {
+            let runs = history
+                .iter()
+                .filter(|entry| entry.day == day && entry.part == part)
+                .collect::<Vec<_>>();
+            if runs.is_empty() {
+                continue;
+            }
+
+            println!("====== Day {day} part {part} ======");
+            let answer_width = runs
+                .iter()
+                .map(|entry| answer(entry).len())
+                .max()
+                .unwrap_or(0)
+                .max("Answer".len());
+            println!(
+                "{:19}  {:7}  {:answer_width$}  {:>10}  Flags",
+                "Run", "Profile", "Answer", "Time"
+            );
+
+            let first = runs.len().saturating_sub(limit);
+            for (index, entry) in runs.iter().enumerate().skip(first) {
+                let previous = index.checked_sub(1).map(|previous| runs[previous]);
+                let row = format!(
+                    "{:19}  {:7}  {:answer_width$}  {:>10}  {}",
+                    format_timestamp(entry.timestamp),
+                    entry.profile,
+                    answer(entry),
+                    format_duration(entry.total_elapsed()),
+                    describe_flags(&compare(previous, entry, threshold)),
+                );
+                println!("{}", row.trim_end());
+            }
+        }
+    }
+}
+
+fn answer(entry: &Entry) -> &str {
+    entry.answer.as_deref().unwrap_or(&entry.status)
+}
+
+fn describe_flags(flags: &[Flag]) -> String {
+    flags
+        .iter()
+        .map(|flag| flag.describe())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Format milliseconds since the Unix epoch as a UTC date and time, e.g. `2023-12-05 06:00:00`.
+fn format_timestamp(timestamp: u64) -> String {
+    let seconds = timestamp / 1000;
+    let (days, time_of_day) = (seconds / 86_400, seconds % 86_400);
+
+    // Converts days since the epoch to a civil date, from Howard Hinnant's date algorithms
+    let z = days as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{year:04}-{month:02}-{day:02} {:02}:{:02}:{:02}",
+        time_of_day / 3600,
+        time_of_day % 3600 / 60,
+        time_of_day % 60
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(profile: &str, answer: Option<&str>, elapsed_ms: u64) -> Entry {
+        Entry {
+            timestamp: 1_701_756_000_000,
+            profile: profile.to_owned(),
+            day: 6,
+            part: 2,
+            status: if answer.is_some() { "OK" } else { "FAIL" }.to_owned(),
+            answer: answer.map(str::to_owned),
+            parse_elapsed: Duration::from_micros(250),
+            elapsed: Duration::from_millis(elapsed_ms),
+        }
+    }
+
+    #[test]
+    fn append_and_load_round_trip() {
+        let path = std::env::temp_dir().join(format!("run-history-{}.tsv", std::process::id()));
+        let _ = fs::remove_file(&path);
+        let entries = [
+            entry("release", Some("71503"), 10),
+            entry("debug", None, 20),
+        ];
+        append(&path, &entries[..1]).unwrap();
+        append(&path, &entries[1..]).unwrap();
+        let contents = fs::read_to_string(&path).unwrap();
+        let loaded = load(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(contents.matches(HEADER).count(), 1);
+        assert_eq!(loaded.len(), 2);
+        for (loaded, entry) in loaded.iter().zip(&entries) {
+            assert_eq!(loaded.timestamp, entry.timestamp);
+            assert_eq!(loaded.profile, entry.profile);
+            assert_eq!((loaded.day, loaded.part), (entry.day, entry.part));
+            assert_eq!(loaded.status, entry.status);
+            assert_eq!(loaded.answer, entry.answer);
+            assert_eq!(loaded.parse_elapsed, entry.parse_elapsed);
+            assert_eq!(loaded.elapsed, entry.elapsed);
+        }
+    }
+
+    #[test]
+    fn parse_rejects_missing_fields() {
+        assert!(Entry::parse("1701756000000\trelease\t6\t2\tOK\t71503").is_err());
+    }
+
+    #[test]
+    fn flags_changed_answer_and_failing() {
+        let previous = entry("release", Some("71503"), 10);
+        let changed = entry("release", Some("71504"), 10);
+        let failing = entry("release", None, 10);
+
+        assert!(compare(None, &changed, 0.1).is_empty());
+        assert!(compare(Some(&previous), &previous, 0.1).is_empty());
+        assert!(compare(Some(&previous), &changed, 0.1) == [Flag::AnswerChanged]);
+        assert!(compare(Some(&previous), &failing, 0.1) == [Flag::Failing]);
+        assert!(compare(Some(&failing), &previous, 0.1).is_empty());
+    }
+
+    #[test]
+    fn flags_slowdowns_over_threshold() {
+        let previous = entry("release", Some("71503"), 10);
+        let slower = entry("release", Some("71503"), 20);
+        let slightly_slower = entry("release", Some("71503"), 11);
+
+        assert!(matches!(
+            compare(Some(&previous), &slower, 0.1)[..],
+            [Flag::Slower(factor)] if factor > 1.9
+        ));
+        assert!(compare(Some(&previous), &slower, 1.5).is_empty());
+        assert!(compare(Some(&previous), &slightly_slower, 0.2).is_empty());
+    }
+
+    #[test]
+    fn ignores_small_slowdowns_and_other_profiles() {
+        let mut previous = entry("release", Some("71503"), 0);
+        previous.parse_elapsed = Duration::from_micros(100);
+        let mut tiny = previous.clone();
+        tiny.parse_elapsed = Duration::from_micros(900);
+        let debug = entry("debug", Some("71503"), 100);
+
+        assert!(compare(Some(&previous), &tiny, 0.1).is_empty());
+        assert!(compare(Some(&previous), &debug, 0.1).is_empty());
+    }
+
+    #[test]
+    fn known_timestamps() {
+        assert_eq!(format_timestamp(0), "1970-01-01 00:00:00");
+        assert_eq!(format_timestamp(1_701_756_000_000), "2023-12-05 06:00:00");
+        assert_eq!(format_timestamp(1_709_164_800_999), "2024-02-29 00:00:00");
+        assert_eq!(format_timestamp(1_709_251_199_000), "2024-02-29 23:59:59");
+    }
+}
@@ -0,0 +1,194 @@
+use std::{
+    borrow::Cow,
+    fmt, fs,
+    io::{self, BufRead, Cursor, Read},
+    path::PathBuf,
+    sync::Arc,
+};
+
+use anyhow::{bail, Context, Result};
+
+/// Puzzle input handed to a day's solutions.
+///
+/// Wraps any buffered reader, so solutions can be driven from a file, stdin or an in-memory
+/// string alike:
+///
+/// ```
+/// # use advent_of_code_2023::{day1::Day1, Answer, Solutions};
+/// let mut day1 = Day1::default();
+/// let parsed = day1.parse("1abc2\npqr3stu8vwx".into())?;
+/// let answer = day1.part1(&parsed)?;
+/// assert_eq!(answer, Answer::Integer(50));
+/// # anyhow::Ok(())
+/// ```
+pub struct Input<'a>(Box<dyn BufRead + 'a>);
+
+impl<'a> Input<'a> {
+    pub fn new(reader: impl BufRead + 'a) -> Self {
+        Self(Box::new(reader))
+    }
+}
+
+impl<'a> From<&'a str> for Input<'a> {
+    fn from(s: &'a str) -> Self {
+        Self::new(s.as_bytes())
+    }
+}
+
+impl Read for Input<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.0.read(buf)
+    }
+}
+
+impl BufRead for Input<'_> {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        self.0.fill_buf()
+    }
+
+    fn consume(&mut self, amt: usize) {
+        self.0.consume(amt)
+    }
+}
+
+/// The path a day's puzzle input is read from unless told otherwise.
+pub fn default_path(day: usize) -> PathBuf {
+    format!("puzzle-input/day{day}").into()
+}
+
+/// Where a day reads its puzzle input from.
+#[derive(Clone)]
+pub enum InputSource {
+    File(PathBuf),
+    /// Input that has already been read into memory, such as stdin, which can only be read once
+    /// but is needed by both parts.
+    Memory(Arc<[u8]>),
+}
+
+impl InputSource {
+    pub fn default_for(day: usize) -> Self {
+        Self::File(default_path(day))
+    }
+
+    /// Interpret a `--input` argument, where `-` means stdin.
+    pub fn from_arg(arg: &str) -> Result<Self> {
+        if arg == "-" {
+            let mut buffer = Vec::new();
+            io::stdin()
+                .read_to_end(&mut buffer)
+                .context("Unable to read input from stdin")?;
+            Ok(Self::Memory(buffer.into()))
+        } else {
+            Ok(Self::File(arg.into()))
+        }
+    }
+
+    /// Open the input, normalized as described in [`normalize`]. An empty input is an error,
+    /// rather than something for the parsers to trip over.
+    pub fn open(&self) -> Result<Input<'static>> {
+        let (normalized, _) = normalize(&self.read()?);
+        if normalized.is_empty() {
+            bail!("{self} is empty");
+        }
+
+        Ok(Input::new(Cursor::new(normalized)))
+    }
+
+    /// Describe anything about the input worth a warning: that it is empty, or what normalizing
+    /// it changes.
+    pub fn check(&self) -> Result<Option<String>> {
+        let (normalized, fixes) = normalize(&self.read()?);
+        if normalized.is_empty() {
+            return Ok(Some(format!("{self} is empty")));
+        }
+        let Some((last, rest)) = fixes.split_last() else {
+            return Ok(None);
+        };
+
+        let fixes = if rest.is_empty() {
+            last.to_string()
+        } else {
+            format!("{} and {last}", rest.join(", "))
+        };
+        Ok(Some(format!(
+            "{self} has {fixes}, which are removed before solving"
+        )))
+    }
+
+    fn read(&self) -> Result<Cow<'_, [u8]>> {
+        match self {
+            Self::File(path) => {
+                Ok(Cow::Owned(fs::read(path).with_context(|| {
+                    format!("Unable to read input file {}", path.display())
+                })?))
+            }
+            Self::Memory(bytes) => Ok(Cow::Borrowed(bytes)),
+        }
+    }
+}
+
+impl fmt::Display for InputSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::File(path) => write!(f, "Input file {}", path.display()),
+            Self::Memory(_) => write!(f, "Input from stdin"),
+        }
+    }
+}
+
+/// Normalize puzzle input to plain `\n`-terminated lines, the way the parsers expect it, by
+/// removing a UTF-8 byte order mark, turning CRLF line endings into LF, and trimming whitespace at
+/// the end of lines and blank lines at the end of the input. Returns the normalized input along
+/// with a description of each of those that applied.
+///
+/// ```
+/// # use advent_of_code_2023::input::normalize;
+/// let (normalized, fixes) = normalize(b"\xEF\xBB\xBFseeds: 79 14\r\n\r\n");
+/// assert_eq!(normalized, b"seeds: 79 14\n");
+/// assert_eq!(fixes, ["a UTF-8 byte order mark", "CRLF line endings", "trailing blank lines"]);
+/// ```
+pub fn normalize(bytes: &[u8]) -> (Vec<u8>, Vec<&'static str>) {
+    let mut fixes = Vec::new();
+    let bytes = match bytes.strip_prefix(b"\xEF\xBB\xBF") {
+        Some(bytes) => {
+            fixes.push("a UTF-8 byte order mark");
+            bytes
+        }
+        None => bytes,
+    };
+
+    let mut crlf = false;
+    let mut trailing_whitespace = false;
+    let mut lines = bytes
+        .strip_suffix(b"\n")
+        .unwrap_or(bytes)
+        .split(|&byte| byte == b'\n')
+        .map(|line| {
+            let without_cr = line.strip_suffix(b"\r").unwrap_or(line);
+            crlf |= without_cr.len() != line.len();
+            let trimmed = without_cr.trim_ascii_end();
+            trailing_whitespace |= trimmed.len() != without_cr.len();
+            trimmed
+        })
+        .collect::<Vec<_>>();
+    if crlf {
+        fixes.push("CRLF line endings");
+    }
+    if trailing_whitespace {
+        fixes.push("trailing whitespace");
+    }
+
+    if lines.last().is_some_and(|line| line.is_empty()) && lines.len() > 1 {
+        fixes.push("trailing blank lines");
+    }
+    while lines.last().is_some_and(|line| line.is_empty()) {
+        lines.pop();
+    }
+
+    let mut normalized = lines.join(&b'\n');
+    if !normalized.is_empty() {
+        normalized.push(b'\n');
+    }
+
+    (normalized, fixes)
+}
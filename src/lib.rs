@@ -0,0 +1,456 @@
+//! Solutions for Advent of Code 2023, along with the machinery to run, time and verify them.
+//!
+//! The `advent-of-code-2023` binary is a thin command line wrapper around this library.
+
+pub mod allocations;
+pub mod answer;
+pub mod answers;
+pub mod bench;
+pub mod differential;
+pub mod examples;
+pub mod generate;
+pub mod history;
+pub mod input;
+pub mod parse;
+pub mod pool;
+pub mod report;
+pub mod trace;
+pub mod verify;
+
+pub mod day1;
+pub mod day2;
+pub mod day3;
+pub mod day4;
+pub mod day5;
+pub mod day6;
+pub mod day7;
+
+use std::{
+    any::Any,
+    panic::{self, AssertUnwindSafe},
+    sync::{mpsc, Arc},
+    thread,
+    time::{Duration, Instant},
+};
+
+use anyhow::{anyhow, bail, Context, Result};
+use itertools::Itertools;
+
+use crate::generate::{Knobs, Rng};
+use crate::input::InputSource;
+use crate::report::{Abort, DayReport, PartReport};
+use crate::trace::Trace;
+
+pub use crate::answer::Answer;
+pub use crate::input::Input;
+
+/// The solutions to both parts of a day.
+///
+/// The input is parsed once by [`Solutions::parse`], and both parts are solved from the same parsed
+/// input. They are `Send`, and the parsed input `Sync`, so days and parts can be run in parallel.
+pub trait Solutions: Send {
+    /// The puzzle input parsed into whatever model both parts work on.
+    type Parsed: Send + Sync + 'static;
+
+    fn parse(&mut self, input: Input<'_>) -> Result<Self::Parsed>;
+    fn part1(&mut self, parsed: &Self::Parsed) -> Result<Answer>;
+    fn part2(&mut self, parsed: &Self::Parsed) -> Result<Answer>;
+
+    /// Hand the solutions a [`Trace`] to emit intermediate values into while solving, which is
+    /// done before every part. Solutions that don't trace anything can ignore it.
+    fn set_trace(&mut self, trace: Trace) {
+        let _ = trace;
+    }
+
+    /// Generate a random but valid puzzle input from `rng`, sized by the `knobs` that apply to
+    /// the day. Returns `None` if the day has no generator.
+    fn generate(&self, rng: &mut Rng, knobs: &mut Knobs) -> Option<String> {
+        let _ = (rng, knobs);
+        None
+    }
+
+    /// Solve `part` the slow but obviously correct way, as a reference to check the real
+    /// solution against on generated inputs. Returns `None` if the part has no oracle, and an
+    /// error for inputs the puzzle rules out.
+    fn oracle(&mut self, part: u8, parsed: &Self::Parsed) -> Option<Result<Answer>> {
+        let _ = (part, parsed);
+        None
+    }
+}
+
+/// Parsed input of any day, as handed around by the runner.
+pub type Parsed = Arc<dyn Any + Send + Sync>;
+
+/// [`Solutions`] with the type of the parsed input erased, so days can be kept in the registry.
+pub trait AnySolutions: Send {
+    fn parse(&mut self, input: Input<'_>) -> Result<Parsed>;
+    fn part(&mut self, part: u8, parsed: &Parsed) -> Result<Answer>;
+    fn set_trace(&mut self, trace: Trace);
+    fn generate(&self, rng: &mut Rng, knobs: &mut Knobs) -> Option<String>;
+    fn oracle(&mut self, part: u8, parsed: &Parsed) -> Option<Result<Answer>>;
+}
+
+impl<S: Solutions> AnySolutions for S {
+    fn parse(&mut self, input: Input<'_>) -> Result<Parsed> {
+        Ok(Arc::new(Solutions::parse(self, input)?))
+    }
+
+    fn part(&mut self, part: u8, parsed: &Parsed) -> Result<Answer> {
+        let parsed = parsed
+            .downcast_ref()
+            .context("Parsed input is of the wrong type")?;
+        match part {
+            1 => self.part1(parsed),
+            2 => self.part2(parsed),
+            _ => bail!("Invalid part {part}"),
+        }
+    }
+
+    fn set_trace(&mut self, trace: Trace) {
+        Solutions::set_trace(self, trace);
+    }
+
+    fn generate(&self, rng: &mut Rng, knobs: &mut Knobs) -> Option<String> {
+        Solutions::generate(self, rng, knobs)
+    }
+
+    fn oracle(&mut self, part: u8, parsed: &Parsed) -> Option<Result<Answer>> {
+        match parsed.downcast_ref() {
+            Some(parsed) => Solutions::oracle(self, part, parsed),
+            None => Some(Err(anyhow!("Parsed input is of the wrong type"))),
+        }
+    }
+}
+
+/// A day's solutions together with the input to run them on.
+pub struct Day {
+    pub day: usize,
+    pub solutions: Box<dyn AnySolutions>,
+    pub input: InputSource,
+    /// Whether to collect the trace events the solutions emit while solving a part.
+    pub trace: bool,
+    registration: &'static Registration,
+}
+
+impl Day {
+    /// A copy of this day with its own fresh solutions, so it can run on another thread.
+    pub fn fork(&self) -> Self {
+        Self {
+            day: self.day,
+            solutions: (self.registration.solutions)(),
+            input: self.input.clone(),
+            trace: self.trace,
+            registration: self.registration,
+        }
+    }
+
+    /// Open and parse the input.
+    pub fn parse(&mut self) -> Result<Parsed> {
+        self.solutions.parse(self.input.open()?)
+    }
+
+    pub fn run_part(&mut self, part: u8, parsed: &Parsed) -> PartReport {
+        let trace = if self.trace {
+            Trace::enabled()
+        } else {
+            Trace::default()
+        };
+        self.solutions.set_trace(trace.clone());
+
+        let start = Instant::now();
+        let (answer, allocations) = allocations::measure(|| self.solutions.part(part, parsed));
+
+        PartReport {
+            part,
+            answer,
+            elapsed: start.elapsed(),
+            allocations,
+            trace: trace.take(),
+        }
+    }
+
+    /// Parse the input, then run the given parts of this day on it, in order, with parsing and
+    /// every part isolated as in [`Day::run_part_isolated`].
+    pub fn run_isolated(&self, parts: &[u8], timeout: Option<Duration>) -> DayReport {
+        let (parsed, parse_elapsed) = self.parse_isolated(timeout);
+
+        match parsed {
+            Ok(parsed) => DayReport {
+                day: self.day,
+                parse_elapsed,
+                parts: parts
+                    .iter()
+                    .map(|&part| self.run_part_isolated(part, &parsed, timeout))
+                    .collect(),
+            },
+            Err(error) => DayReport::parse_failed(self.day, parts, error, parse_elapsed),
+        }
+    }
+
+    /// Parse the input on a thread of its own, like [`Day::run_part_isolated`] does for parts.
+    /// Returns how long parsing took along with the result.
+    pub fn parse_isolated(&self, timeout: Option<Duration>) -> (Result<Parsed>, Duration) {
+        let mut day = self.fork();
+        let start = Instant::now();
+        let result = isolated(format!("day{} parse", self.day), timeout, move || {
+            let start = Instant::now();
+            let parsed = day.parse();
+            (parsed, start.elapsed())
+        });
+
+        match result {
+            Ok(parsed) => parsed,
+            Err(error) => (Err(error), start.elapsed()),
+        }
+    }
+
+    /// Run a part on a thread of its own, so a panic or a part that takes longer than `timeout`
+    /// is reported as an [`Abort`] instead of taking everything else down with it.
+    ///
+    /// A part that times out can't be stopped, so its thread is left running in the background
+    /// until the process exits.
+    pub fn run_part_isolated(
+        &self,
+        part: u8,
+        parsed: &Parsed,
+        timeout: Option<Duration>,
+    ) -> PartReport {
+        let mut day = self.fork();
+        let parsed = parsed.clone();
+        let start = Instant::now();
+        let result = isolated(
+            format!("day{} part{}", self.day, part),
+            timeout,
+            move || day.run_part(part, &parsed),
+        );
+
+        result.unwrap_or_else(|error| PartReport {
+            part,
+            answer: Err(error),
+            elapsed: start.elapsed(),
+            allocations: None,
+            trace: Vec::new(),
+        })
+    }
+}
+
+/// Run `f` on a thread called `name`, turning a panic or taking longer than `timeout` into an
+/// [`Abort`].
+fn isolated<T, F>(name: String, timeout: Option<Duration>, f: F) -> Result<T>
+where
+    T: Send + 'static,
+    F: FnOnce() -> T + Send + 'static,
+{
+    let (sender, receiver) = mpsc::channel();
+    thread::Builder::new()
+        .name(name)
+        .spawn(move || {
+            let result = panic::catch_unwind(AssertUnwindSafe(f));
+            // The receiver is gone if it timed out, in which case nobody is interested
+            let _ = sender.send(result);
+        })
+        .context("Unable to spawn thread")?;
+
+    let result = match timeout {
+        Some(timeout) => receiver.recv_timeout(timeout).ok(),
+        None => receiver.recv().ok(),
+    };
+    match result {
+        Some(Ok(value)) => Ok(value),
+        Some(Err(payload)) => Err(Abort::Panicked(panic_message(payload.as_ref())).into()),
+        None => Err(Abort::TimedOut(timeout.unwrap_or_default()).into()),
+    }
+}
+
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "Unknown panic payload".to_owned()
+    }
+}
+
+/// Register the solutions of the day modules declared above in [`DAYS`], so adding a day is a
+/// `pub mod` declaration and a single line in the invocation below.
+macro_rules! days {
+    ($($day:literal => $module:ident::$solutions:ident),* $(,)?) => {
+        /// Every day that has solutions, ordered by day number.
+        pub static DAYS: &[Registration] = &[
+            $(Registration {
+                day: $day,
+                name: stringify!($module),
+                solutions: new_solutions::<$module::$solutions>,
+            },)*
+        ];
+    };
+}
+
+days! {
+    1 => day1::Day1,
+    2 => day2::Day2,
+    3 => day3::Day3,
+    4 => day4::Day4,
+    5 => day5::Day5,
+    6 => day6::Day6,
+    7 => day7::Day7,
+}
+
+const _: () = assert!(
+    days_are_ordered(DAYS),
+    "Days must be registered in order, and only once"
+);
+
+/// An entry in the day registry.
+pub struct Registration {
+    pub day: usize,
+    /// The name of the day's module, e.g. `day3`.
+    pub name: &'static str,
+    pub solutions: fn() -> Box<dyn AnySolutions>,
+}
+
+fn new_solutions<S>() -> Box<dyn AnySolutions>
+where
+    S: Solutions + Default + 'static,
+{
+    Box::<S>::default()
+}
+
+const fn days_are_ordered(days: &[Registration]) -> bool {
+    let mut i = 1;
+    while i < days.len() {
+        if days[i - 1].day >= days[i].day {
+            return false;
+        }
+        i += 1;
+    }
+
+    true
+}
+
+/// Look up the solutions for `day`, reading input from `puzzle-input/day#` by default.
+pub fn day(day: usize) -> Option<Day> {
+    let registration = DAYS.iter().find(|registration| registration.day == day)?;
+
+    Some(Day {
+        day,
+        solutions: (registration.solutions)(),
+        input: InputSource::default_for(day),
+        trace: false,
+        registration,
+    })
+}
+
+/// Run the given parts of all `days` on a pool of `threads` worker threads. First the input of
+/// every day is parsed, then every part of every day is run as a separate task, with parsing and
+/// parts isolated as in [`Day::run_part_isolated`]. The reports come back in the same order as
+/// `days`.
+pub fn run_parallel(
+    days: &[Day],
+    parts: &[u8],
+    threads: usize,
+    timeout: Option<Duration>,
+) -> Vec<DayReport> {
+    let parse_tasks = days
+        .iter()
+        .map(|day| {
+            let day = day.fork();
+            move || day.parse_isolated(timeout)
+        })
+        .collect::<Vec<_>>();
+    let parsed = pool::run_ordered(parse_tasks, threads);
+
+    let part_tasks = days
+        .iter()
+        .zip(&parsed)
+        .filter_map(|(day, (parsed, _))| Some((day, parsed.as_ref().ok()?)))
+        .flat_map(|(day, parsed)| {
+            parts.iter().map(move |&part| {
+                let day = day.fork();
+                let parsed = parsed.clone();
+                move || day.run_part_isolated(part, &parsed, timeout)
+            })
+        })
+        .collect::<Vec<_>>();
+    let mut part_reports = pool::run_ordered(part_tasks, threads).into_iter();
+
+    days.iter()
+        .zip(parsed)
+        .map(|(day, (parsed, parse_elapsed))| match parsed {
+            Ok(_) => DayReport {
+                day: day.day,
+                parse_elapsed,
+                parts: part_reports.by_ref().take(parts.len()).collect(),
+            },
+            Err(error) => DayReport::parse_failed(day.day, parts, error, parse_elapsed),
+        })
+        .collect()
+}
+
+/// Parse a day selection such as `3`, `all` or `1-5,7` into a sorted list of day numbers.
+///
+/// `all` selects every day that has solutions. Explicitly named days are checked to exist.
+pub fn parse_day_selection(s: &str) -> Result<Vec<usize>> {
+    if s == "all" {
+        return Ok(DAYS.iter().map(|registration| registration.day).collect());
+    }
+
+    let mut days = Vec::new();
+    for part in s.split(',') {
+        let (start, end) = match part.split_once('-') {
+            Some((start, end)) => (start, end),
+            None => (part, part),
+        };
+        let start: usize = start
+            .trim()
+            .parse()
+            .with_context(|| format!("Unable to parse \"{start}\" as a day number"))?;
+        let end: usize = end
+            .trim()
+            .parse()
+            .with_context(|| format!("Unable to parse \"{end}\" as a day number"))?;
+        if start > end {
+            bail!("Invalid day range {start}-{end}");
+        }
+
+        for day_number in start..=end {
+            if day(day_number).is_none() {
+                bail!(
+                    "Day {day_number} not found. Available days: {}",
+                    DAYS.iter().map(|registration| registration.day).join(", ")
+                );
+            }
+            days.push(day_number);
+        }
+    }
+    days.sort_unstable();
+    days.dedup();
+
+    Ok(days)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn day_selection() {
+        assert_eq!(parse_day_selection("3").unwrap(), [3]);
+        assert_eq!(parse_day_selection("5,1-3, 2").unwrap(), [1, 2, 3, 5]);
+        assert_eq!(
+            parse_day_selection("all").unwrap(),
+            DAYS.iter()
+                .map(|registration| registration.day)
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn invalid_day_selection() {
+        assert!(parse_day_selection("").is_err());
+        assert!(parse_day_selection("3-1").is_err());
+        assert!(parse_day_selection("1-x").is_err());
+        assert!(parse_day_selection("26").is_err());
+    }
+}
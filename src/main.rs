@@ -1,80 +1,350 @@
-mod day1;
-mod day2;
-mod day3;
-mod day4;
-mod day5;
-mod day6;
-mod day7;
+mod cli;
+mod scaffold;
+mod watch;
 
-use std::{env, fs::File, io::BufReader};
+use std::{env, panic, path::Path, slice};
 
-use anyhow::{Context, Result};
+use advent_of_code_2023::{
+    answers::Answers,
+    bench, day, differential, examples, generate, history,
+    input::{self, InputSource},
+    report::{self, DayReport, Format},
+    verify, Day, DAYS,
+};
+use anyhow::{bail, Context, Result};
 
-use crate::day6::Day6;
-use crate::day7::Day7;
-use crate::{day1::Day1, day2::Day2, day3::Day3, day4::Day4, day5::Day5};
+use crate::cli::{Command, Selection};
 
-pub type Input = BufReader<File>;
+#[cfg(feature = "count-allocations")]
+#[global_allocator]
+static ALLOCATOR: advent_of_code_2023::allocations::CountingAllocator =
+    advent_of_code_2023::allocations::CountingAllocator;
 
-pub trait Solutions {
-    fn part1(&mut self, input: Input) -> Result<String>;
-    fn part2(&mut self, input: Input) -> Result<String>;
+fn main() -> Result<()> {
+    match Command::parse(env::args().skip(1))? {
+        Command::Run {
+            selection,
+            example: true,
+            ..
+        } => run_examples(&selection),
+        Command::Run {
+            selection, format, ..
+        } => run(&selection, format),
+        Command::Bench {
+            selection,
+            iterations,
+        } => {
+            let timings = load_days(&selection)?
+                .iter()
+                .flat_map(|day| {
+                    bench::bench_day(day, &selection.parts, iterations, selection.timeout)
+                })
+                .collect::<Vec<_>>();
+            bench::print_timings(&timings);
+
+            Ok(())
+        }
+        Command::Verify { selection, answers } => {
+            let answers = Answers::load(&answers)?;
+            let reports = run_parallel(&selection)?;
+            let verifications = verify::verify(&reports, &answers);
+            verify::print_verifications(&verifications);
+
+            let failures = verifications
+                .iter()
+                .filter(|verification| verification.verdict == verify::Verdict::Fail)
+                .count();
+            if failures > 0 {
+                bail!("{failures} part(s) did not match the expected answer");
+            }
+
+            Ok(())
+        }
+        Command::Record {
+            selection,
+            answers: answers_path,
+        } => {
+            let mut answers = Answers::load_or_default(&answers_path)?;
+            let reports = run_parallel(&selection)?;
+            let failures = verify::record(&reports, &mut answers);
+            answers.save(&answers_path)?;
+
+            if failures > 0 {
+                bail!("{failures} part(s) failed and were not recorded");
+            }
+
+            Ok(())
+        }
+        Command::Watch {
+            selection,
+            interval,
+        } => {
+            let day = load_days(&selection)?
+                .pop()
+                .context("Watch needs exactly one day")?;
+            watch::watch(day, &selection.parts, selection.timeout, interval)
+        }
+        Command::Inputs {
+            selection,
+            directory,
+        } => run_inputs(&selection, &directory),
+        Command::History {
+            selection,
+            limit,
+            threshold,
+        } => {
+            let history = history::load(Path::new(history::DEFAULT_PATH))?;
+            history::print_history(
+                &history,
+                &selection.days,
+                &selection.parts,
+                limit,
+                threshold,
+            );
+
+            Ok(())
+        }
+        Command::Compare {
+            selection,
+            threshold,
+        } => {
+            let history = history::load(Path::new(history::DEFAULT_PATH))?;
+            let reports = run_parallel(&selection)?;
+            let entries = history::entries(&reports);
+            let comparisons = history::compare_with_history(&history, &entries, threshold);
+            history::print_comparisons(&comparisons);
+
+            let changed = comparisons
+                .iter()
+                .filter(|comparison| comparison.answer_changed())
+                .count();
+            if changed > 0 {
+                bail!("{changed} part(s) changed their answer since the last run");
+            }
+
+            Ok(())
+        }
+        Command::Generate {
+            day,
+            seed,
+            count,
+            knobs,
+            output,
+        } => {
+            let day = day_by_number(day)?;
+            generate::write_inputs(&day, seed..seed.saturating_add(count), &knobs, &output)
+        }
+        Command::Fuzz {
+            selection,
+            seed,
+            cases,
+            knobs,
+        } => {
+            // Panics of the parts being checked are reported as their answers
+            let hook = panic::take_hook();
+            panic::set_hook(Box::new(move |info| {
+                if !differential::is_worker_thread() {
+                    hook(info);
+                }
+            }));
+
+            let mut divergences = 0;
+            for day in load_days(&selection)? {
+                for &part in &selection.parts {
+                    let outcome = differential::check(
+                        &day,
+                        part,
+                        seed..seed.saturating_add(cases),
+                        &knobs,
+                        selection.timeout,
+                    )
+                    .with_context(|| format!("Unable to check day {} part {part}", day.day))?;
+                    differential::print_outcome(day.day, part, &outcome);
+                    if matches!(outcome, differential::Outcome::Diverged(_)) {
+                        divergences += 1;
+                    }
+                }
+            }
+
+            if divergences > 0 {
+                bail!("{divergences} part(s) diverged from their oracle");
+            }
+
+            Ok(())
+        }
+        Command::New { day } => scaffold::new_day(day),
+        Command::List => {
+            for registration in DAYS {
+                let path = input::default_path(registration.day);
+                let status = if path.exists() { "" } else { " (missing)" };
+                println!(
+                    "Day {:>2}  {:5}  input: {}{}",
+                    registration.day,
+                    registration.name,
+                    path.display(),
+                    status
+                );
+            }
+
+            Ok(())
+        }
+    }
+}
+
+/// Run the selected days and parts in parallel on `--jobs` threads, and record the run in the
+/// history.
+fn run_parallel(selection: &Selection) -> Result<Vec<DayReport>> {
+    let days = load_days(selection)?;
+    let reports = advent_of_code_2023::run_parallel(
+        &days,
+        &selection.parts,
+        selection.jobs,
+        selection.timeout,
+    );
+
+    // Only runs on the real input are comparable with each other, and tracing slows parts down
+    if selection.input.is_none() && !selection.trace {
+        let entries = history::entries(&reports);
+        if let Err(error) = history::append(Path::new(history::DEFAULT_PATH), &entries) {
+            eprintln!("Warning: Unable to record the run in the history: {error:#}");
+        }
+    }
+
+    Ok(reports)
 }
 
-struct Day {
-    day: usize,
-    solutions: Box<dyn Solutions>,
+/// Look up the selected days, pointing them at the `--input` override if there is one.
+fn load_days(selection: &Selection) -> Result<Vec<Day>> {
+    let input = selection
+        .input
+        .as_deref()
+        .map(InputSource::from_arg)
+        .transpose()?;
+
+    let days = selection
+        .days
+        .iter()
+        .filter_map(|&day_number| day(day_number))
+        .map(|mut day| {
+            if let Some(input) = &input {
+                day.input = input.clone();
+            }
+            day.trace = selection.trace;
+            day
+        })
+        .collect::<Vec<_>>();
+    warn_about_inputs(&days);
+
+    Ok(days)
 }
 
-impl Day {
-    fn run(&mut self) -> Result<()> {
-        println!("====== Day {} ======", self.day);
-        println!("Part 1: {}", self.solutions.part1(read_input(self.day)?)?);
-        println!("Part 2: {}", self.solutions.part2(read_input(self.day)?)?);
+fn day_by_number(day_number: usize) -> Result<Day> {
+    day(day_number).with_context(|| format!("Day {day_number} not found"))
+}
 
-        Ok(())
+/// Warn about empty inputs, or inputs that are normalized before solving. Inputs that can't be
+/// read are left for running the day to report.
+fn warn_about_inputs(days: &[Day]) {
+    for day in days {
+        if let Ok(Some(warning)) = day.input.check() {
+            eprintln!("Warning: {warning}");
+        }
     }
 }
 
-fn day(day: usize) -> Option<Day> {
-    fn load<S>() -> Option<Box<dyn Solutions>>
-    where
-        S: Solutions + Default + 'static,
-    {
-        Some(Box::<S>::default())
+fn run(selection: &Selection, format: Format) -> Result<()> {
+    let reports = run_parallel(selection)?;
+
+    match (format, reports.as_slice()) {
+        (Format::Text, [report]) => report::print_day(report),
+        (Format::Text, reports) => report::print_summary(reports),
+        (Format::Json, reports) => report::print_json(reports),
+        (Format::Csv, reports) => report::print_csv(reports),
+    }
+    if matches!(format, Format::Text) {
+        report::print_traces(&reports);
+    }
+
+    let failures = reports
+        .iter()
+        .flat_map(|report| &report.parts)
+        .filter(|part| part.answer.is_err())
+        .count();
+    if failures > 0 {
+        bail!("{failures} part(s) failed");
     }
 
-    let solutions = match day {
-        1 => load::<Day1>(),
-        2 => load::<Day2>(),
-        3 => load::<Day3>(),
-        4 => load::<Day4>(),
-        5 => load::<Day5>(),
-        6 => load::<Day6>(),
-        7 => load::<Day7>(),
-        _ => None,
-    }?;
-
-    Some(Day { day, solutions })
+    Ok(())
 }
 
-fn main() -> Result<()> {
-    let day_number = env::args()
-        .nth(1)
-        .context(
-            "Too few arguments. Please pass a day number as the first argument."
-        )?
-        .parse()
-        .context("Unable to parse first argument as number. Please pass a day number as the first argument.")?;
+/// Run every day on each of its examples, checking them against their expected answers.
+fn run_examples(selection: &Selection) -> Result<()> {
+    let mut failures = 0;
+    for mut day in load_days(selection)? {
+        let examples = examples::find_examples(day.day)?;
+        if examples.is_empty() {
+            println!("====== Day {}: no examples found ======", day.day);
+        }
 
-    let mut day = day(day_number).context("Day not found")?;
-    day.run()?;
+        for example in examples {
+            println!("====== {} ======", example.name);
+            day.input = InputSource::File(example.path.clone());
+            let report =
+                day.run_isolated(&example.parts(day.day, &selection.parts), selection.timeout);
+            match &example.answers {
+                Some(answers) => {
+                    let verifications = verify::verify(slice::from_ref(&report), answers);
+                    verify::print_verifications(&verifications);
+                    failures += verifications
+                        .iter()
+                        .filter(|verification| verification.verdict != verify::Verdict::Pass)
+                        .count();
+                }
+                None => report::print_parts(&report),
+            }
+            report::print_traces(slice::from_ref(&report));
+        }
+    }
+
+    if failures > 0 {
+        bail!("{failures} example part(s) did not match the expected answer");
+    }
 
     Ok(())
 }
 
-fn read_input(day: usize) -> Result<Input> {
-    Ok(BufReader::new(
-        File::open(format!("puzzle-input/day{day}")).context("Unable to read input file")?,
-    ))
+/// Run a day on every input in `directory`, and print a table of the results of every input and
+/// part, followed by the errors of any failed parts.
+fn run_inputs(selection: &Selection, directory: &Path) -> Result<()> {
+    let day_number = selection.days[0];
+    let inputs = examples::find_inputs(directory, day_number)?;
+    if inputs.is_empty() {
+        bail!("No inputs found in {}", directory.display());
+    }
+
+    let days = inputs
+        .iter()
+        .map(|input| {
+            let mut day = day_by_number(day_number)?;
+            day.input = InputSource::File(input.path.clone());
+            Ok(day)
+        })
+        .collect::<Result<Vec<_>>>()?;
+    warn_about_inputs(&days);
+    let reports = advent_of_code_2023::run_parallel(
+        &days,
+        &selection.parts,
+        selection.jobs,
+        selection.timeout,
+    );
+
+    let results = examples::input_results(&inputs, &reports);
+    examples::print_input_results(&results);
+
+    let failures = results.iter().filter(|result| result.failed()).count();
+    if failures > 0 {
+        bail!("{failures} input part(s) failed");
+    }
+
+    Ok(())
 }
@@ -0,0 +1,205 @@
+use std::{
+    error::Error,
+    fmt::{self, Write},
+    str::FromStr,
+};
+
+/// A parse error pointing at the offending text in a line of puzzle input.
+///
+/// Parsers create these from the line they are parsing and a slice of it, so the column can be
+/// worked out from where the slice is. The line number is usually not known at that point, and is
+/// filled in with [`ParseError::on_line`] by whoever is going through the lines.
+#[derive(Clone, Debug)]
+pub struct ParseError {
+    pub message: String,
+    /// The 1-based line number, if known.
+    pub line_number: Option<usize>,
+    /// The 1-based column of the offending text, counted in characters.
+    pub column: usize,
+    /// The offending text itself.
+    pub text: String,
+    /// The whole line the offending text is in.
+    pub source_line: String,
+}
+
+impl ParseError {
+    /// An error about `span`, which should be a slice of `line`. If it isn't, the error points at
+    /// the start of the line.
+    pub fn at(line: &str, span: &str, message: impl Into<String>) -> Self {
+        let offset = offset_in(line, span).unwrap_or(0);
+
+        Self {
+            message: message.into(),
+            line_number: None,
+            column: line[..offset].chars().count() + 1,
+            text: span.to_owned(),
+            source_line: line.to_owned(),
+        }
+    }
+
+    /// An error about something missing at the end of `line`.
+    pub fn at_end(line: &str, message: impl Into<String>) -> Self {
+        Self::at(line, &line[line.len()..], message)
+    }
+
+    /// Set the 1-based line number the error was found on.
+    pub fn on_line(mut self, line_number: usize) -> Self {
+        self.line_number = Some(line_number);
+        self
+    }
+
+    /// Turn an error about `inner`, a slice of `outer`, into one about `outer`. Used when a parser
+    /// for a whole line hands part of it to another parser.
+    pub fn within(mut self, outer: &str, inner: &str) -> Self {
+        if let Some(offset) = offset_in(outer, inner) {
+            self.column += outer[..offset].chars().count();
+            self.source_line = outer.to_owned();
+        }
+        self
+    }
+
+    /// Render the error with the offending line and a caret under the offending text:
+    ///
+    /// ```text
+    /// Unable to parse amount
+    ///  --> line 3, column 9
+    ///   |
+    /// 3 | Game 3: x green, 6 blue
+    ///   |         ^
+    /// ```
+    pub fn render(&self) -> String {
+        let line_number = self
+            .line_number
+            .map_or_else(|| "?".to_owned(), |n| n.to_string());
+        let gutter = " ".repeat(line_number.len());
+        let padding = " ".repeat(self.column - 1);
+        let carets = "^".repeat(self.text.chars().count().max(1));
+
+        let mut rendered = String::new();
+        let _ = writeln!(rendered, "{}", self.message);
+        let _ = writeln!(
+            rendered,
+            "{gutter}--> line {line_number}, column {}",
+            self.column
+        );
+        let _ = writeln!(rendered, "{gutter} |");
+        let _ = writeln!(rendered, "{line_number} | {}", self.source_line);
+        let _ = write!(rendered, "{gutter} | {padding}{carets}");
+
+        rendered
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at ", self.message)?;
+        if let Some(line_number) = self.line_number {
+            write!(f, "line {line_number}, ")?;
+        }
+        write!(f, "column {}", self.column)?;
+        if !self.text.is_empty() {
+            write!(f, ": \"{}\"", self.text)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Error for ParseError {}
+
+/// Parse `span`, a slice of `line`, reporting failure as a [`ParseError`] pointing at it.
+pub fn parse_at<T>(line: &str, span: &str, what: &str) -> Result<T, ParseError>
+where
+    T: FromStr,
+    T::Err: fmt::Display,
+{
+    span.parse()
+        .map_err(|error| ParseError::at(line, span, format!("Unable to parse {what}: {error}")))
+}
+
+/// Set the line number of `error` if it is a [`ParseError`], for parsers that return other errors
+/// too.
+pub fn with_line_number(mut error: anyhow::Error, line_number: usize) -> anyhow::Error {
+    if let Some(parse_error) = error.downcast_mut::<ParseError>() {
+        parse_error.line_number = Some(line_number);
+    }
+
+    error
+}
+
+/// Find the first [`ParseError`] in an error's chain of causes.
+pub fn find_parse_error(error: &anyhow::Error) -> Option<&ParseError> {
+    crate::report::find_cause(error)
+}
+
+fn offset_in(outer: &str, inner: &str) -> Option<usize> {
+    let offset = (inner.as_ptr() as usize).checked_sub(outer.as_ptr() as usize)?;
+    (offset + inner.len() <= outer.len()).then_some(offset)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn column_of_span() {
+        let line = "Game 3: x green";
+        let error = ParseError::at(line, &line[8..9], "Unable to parse amount");
+        assert_eq!(error.column, 9);
+        assert_eq!(error.text, "x");
+    }
+
+    #[test]
+    fn column_counts_characters() {
+        let line = "ééé 12x";
+        let error = ParseError::at(line, &line[line.len() - 3..], "Unable to parse number");
+        assert_eq!(error.column, 5);
+    }
+
+    #[test]
+    fn column_of_foreign_span() {
+        let error = ParseError::at("Game 3", "3", "Unable to parse id");
+        assert_eq!(error.column, 1);
+    }
+
+    #[test]
+    fn column_at_end() {
+        let error = ParseError::at_end("Game 3:", "Expected a set");
+        assert_eq!(error.column, 8);
+        assert!(error.text.is_empty());
+    }
+
+    #[test]
+    fn column_within_outer() {
+        let outer = "Game 3: x green, 6 blue";
+        let inner = &outer[8..];
+        let error =
+            ParseError::at(inner, &inner[..1], "Unable to parse amount").within(outer, inner);
+        assert_eq!(error.column, 9);
+        assert_eq!(error.source_line, outer);
+    }
+
+    #[test]
+    fn column_within_foreign_outer() {
+        let outer = String::from("Game 3: x green");
+        let inner = "x green";
+        let error = ParseError::at(inner, &inner[2..], "Unknown color").within(&outer, inner);
+        assert_eq!(error.column, 3);
+        assert_eq!(error.source_line, inner);
+    }
+
+    #[test]
+    fn render_points_at_text() {
+        let line = "Game 3: x green, 6 blue";
+        let error = ParseError::at(line, &line[8..9], "Unable to parse amount").on_line(3);
+        assert_eq!(
+            error.render(),
+            "\
+Unable to parse amount
+ --> line 3, column 9
+  |
+3 | Game 3: x green, 6 blue
+  |         ^"
+        );
+    }
+}
@@ -0,0 +1,39 @@
+use std::{
+    num::NonZeroUsize,
+    sync::Mutex,
+    thread::{self, available_parallelism},
+};
+
+/// The number of worker threads to use when not told otherwise.
+pub fn default_threads() -> usize {
+    available_parallelism().map_or(1, NonZeroUsize::get)
+}
+
+/// Run `tasks` on up to `threads` worker threads and return their results in the same order as
+/// the tasks, no matter in which order they finish.
+pub fn run_ordered<T, F>(tasks: Vec<F>, threads: usize) -> Vec<T>
+where
+    T: Send,
+    F: FnOnce() -> T + Send,
+{
+    let task_count = tasks.len();
+    let tasks = Mutex::new(tasks.into_iter().enumerate());
+    let results = Mutex::new(Vec::with_capacity(task_count));
+
+    thread::scope(|scope| {
+        for _ in 0..threads.clamp(1, task_count.max(1)) {
+            scope.spawn(|| loop {
+                let Some((index, task)) = tasks.lock().unwrap().next() else {
+                    break;
+                };
+                let result = task();
+                results.lock().unwrap().push((index, result));
+            });
+        }
+    });
+
+    let mut results = results.into_inner().unwrap();
+    results.sort_unstable_by_key(|(index, _)| *index);
+
+    results.into_iter().map(|(_, result)| result).collect()
+}
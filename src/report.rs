@@ -0,0 +1,412 @@
+use std::{fmt, str::FromStr, sync::Arc, time::Duration};
+
+use anyhow::{bail, Error, Result};
+
+use crate::{allocations::AllocationStats, parse::find_parse_error, trace::Event, Answer};
+
+#[derive(Clone, Copy)]
+pub enum Format {
+    Text,
+    Json,
+    Csv,
+}
+
+impl FromStr for Format {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "text" => Self::Text,
+            "json" => Self::Json,
+            "csv" => Self::Csv,
+            _ => bail!("Unknown format \"{s}\", expected text, json or csv"),
+        })
+    }
+}
+
+/// Why a part was aborted instead of returning an answer or an error of its own.
+#[derive(Debug)]
+pub enum Abort {
+    Panicked(String),
+    TimedOut(Duration),
+}
+
+impl fmt::Display for Abort {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Abort::Panicked(message) => write!(f, "Panicked: {message}"),
+            Abort::TimedOut(timeout) => write!(f, "Timed out after {timeout:?}"),
+        }
+    }
+}
+
+impl std::error::Error for Abort {}
+
+pub struct DayReport {
+    pub day: usize,
+    /// How long it took to parse the input, which is shared by all parts.
+    pub parse_elapsed: Duration,
+    pub parts: Vec<PartReport>,
+}
+
+impl DayReport {
+    /// The report of a day whose input couldn't be parsed, so every part fails with the same error.
+    pub fn parse_failed(day: usize, parts: &[u8], error: Error, parse_elapsed: Duration) -> Self {
+        let error = Arc::new(error);
+
+        Self {
+            day,
+            parse_elapsed,
+            parts: parts
+                .iter()
+                .map(|&part| PartReport {
+                    part,
+                    answer: Err(SharedError(error.clone()).into()),
+                    elapsed: Duration::ZERO,
+                    allocations: None,
+                    trace: Vec::new(),
+                })
+                .collect(),
+        }
+    }
+}
+
+/// An error shared by several parts, such as the input failing to parse. It displays and chains
+/// exactly like the error it wraps.
+#[derive(Debug)]
+struct SharedError(Arc<Error>);
+
+impl fmt::Display for SharedError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&**self.0, f)
+    }
+}
+
+impl std::error::Error for SharedError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        self.0.source()
+    }
+}
+
+/// Find the first cause of type `E` in an error's chain of causes, including those of errors
+/// shared between parts.
+pub fn find_cause<E>(error: &Error) -> Option<&E>
+where
+    E: std::error::Error + Send + Sync + 'static,
+{
+    match error.downcast_ref::<SharedError>() {
+        Some(shared) => find_cause(&shared.0),
+        None => error.chain().find_map(|cause| cause.downcast_ref()),
+    }
+}
+
+pub struct PartReport {
+    pub part: u8,
+    pub answer: Result<Answer>,
+    pub elapsed: Duration,
+    /// What the part allocated, if allocations are being counted.
+    pub allocations: Option<AllocationStats>,
+    /// The events emitted while solving the part, if tracing was enabled.
+    pub trace: Vec<Event>,
+}
+
+impl PartReport {
+    /// The error and all of its causes, outermost first.
+    fn error_chain(&self) -> Vec<String> {
+        match &self.answer {
+            Ok(_) => Vec::new(),
+            Err(error) => error.chain().map(ToString::to_string).collect(),
+        }
+    }
+
+    /// OK, FAIL, PANIC or TIMEOUT.
+    pub fn status(&self) -> &'static str {
+        match &self.answer {
+            Ok(_) => "OK",
+            Err(error) => match find_cause::<Abort>(error) {
+                Some(Abort::Panicked(_)) => "PANIC",
+                Some(Abort::TimedOut(_)) => "TIMEOUT",
+                None => "FAIL",
+            },
+        }
+    }
+}
+
+/// Print the results of a single day the same way a plain `cargo run <day>` always has.
+pub fn print_day(report: &DayReport) {
+    println!("====== Day {} ======", report.day);
+    print_parts(report);
+}
+
+/// Print one `Part #: answer` line per part. Parts that panicked or timed out are marked with
+/// their status instead of as an error.
+pub fn print_parts(report: &DayReport) {
+    for part in &report.parts {
+        match &part.answer {
+            Ok(answer) => println!("Part {}: {}", part.part, answer),
+            Err(error) => {
+                let status = match part.status() {
+                    "FAIL" => "error",
+                    status => status,
+                };
+                println!("Part {}: {}: {:#}", part.part, status, error);
+                print_parse_error(error, "");
+            }
+        }
+    }
+}
+
+/// If `error` was caused by a [`ParseError`](crate::parse::ParseError), print it again with the
+/// offending line of input and a caret under the problem, each line prefixed with `indent`.
+pub fn print_parse_error(error: &Error, indent: &str) {
+    if let Some(parse_error) = find_parse_error(error) {
+        for line in parse_error.render().lines() {
+            println!("{indent}{line}");
+        }
+    }
+}
+
+/// Print a table with one row per day and part, followed by the errors of any failed parts.
+pub fn print_summary(reports: &[DayReport]) {
+    let rows = reports
+        .iter()
+        .flat_map(|report| report.parts.iter().map(move |part| (report.day, part)))
+        .collect::<Vec<_>>();
+
+    let answer_width = rows
+        .iter()
+        .filter_map(|(_, part)| part.answer.as_ref().ok())
+        .map(|answer| answer.to_string().len())
+        .max()
+        .unwrap_or(0)
+        .max("Answer".len());
+
+    println!("Day  Part  {:answer_width$}  Status", "Answer");
+    for (day, part) in &rows {
+        // Numbers are right-aligned so their magnitudes line up
+        let answer = match &part.answer {
+            Ok(answer @ Answer::Text(_)) => format!("{answer:answer_width$}"),
+            Ok(answer) => format!("{answer:>answer_width$}"),
+            Err(_) => format!("{:answer_width$}", "-"),
+        };
+        println!(
+            "{:>3}  {:>4}  {}  {}",
+            day,
+            part.part,
+            answer,
+            part.status()
+        );
+    }
+
+    let failures = rows
+        .iter()
+        .filter_map(|(day, part)| Some((day, part.part, part.answer.as_ref().err()?)))
+        .collect::<Vec<_>>();
+    if !failures.is_empty() {
+        println!();
+        println!("Errors:");
+        for (day, part, error) in failures {
+            println!("  Day {day} part {part}: {error:#}");
+            print_parse_error(error, "    ");
+        }
+    }
+}
+
+/// Print a JSON array with one object per day and part. Numeric answers are written as JSON
+/// numbers, even big integers, and text answers as strings. Trace events are included as objects
+/// with string fields.
+pub fn print_json(reports: &[DayReport]) {
+    let records = reports
+        .iter()
+        .flat_map(|report| report.parts.iter().map(move |part| (report, part)))
+        .map(|(report, part)| {
+            let answer = match &part.answer {
+                Ok(answer @ Answer::Text(_)) => json_string(&answer.to_string()),
+                Ok(answer) => answer.to_string(),
+                Err(_) => "null".to_owned(),
+            };
+            let allocations = AllocationColumns::new(part, "null");
+            let trace = part
+                .trace
+                .iter()
+                .map(json_event)
+                .collect::<Vec<_>>()
+                .join(",");
+            let errors = part
+                .error_chain()
+                .iter()
+                .map(|error| json_string(error))
+                .collect::<Vec<_>>()
+                .join(",");
+
+            format!(
+                concat!(
+                    r#"{{"day":{},"part":{},"success":{},"status":"{}","answer":{},"#,
+                    r#""parse_elapsed_ns":{},"elapsed_ns":{},"#,
+                    r#""allocations":{},"allocated_bytes":{},"peak_heap_bytes":{},"#,
+                    r#""errors":[{}],"trace":[{}]}}"#,
+                ),
+                report.day,
+                part.part,
+                part.answer.is_ok(),
+                part.status(),
+                answer,
+                report.parse_elapsed.as_nanos(),
+                part.elapsed.as_nanos(),
+                allocations.allocations,
+                allocations.bytes,
+                allocations.peak,
+                errors,
+                trace,
+            )
+        })
+        .collect::<Vec<_>>();
+
+    println!("[");
+    println!("  {}", records.join(",\n  "));
+    println!("]");
+}
+
+/// Print a CSV table with one row per day and part. The error chain is joined with `: `, the same
+/// way anyhow's alternate formatting does.
+pub fn print_csv(reports: &[DayReport]) {
+    println!(concat!(
+        "day,part,success,status,answer,parse_elapsed_ns,elapsed_ns,",
+        "allocations,allocated_bytes,peak_heap_bytes,error",
+    ));
+    for report in reports {
+        for part in &report.parts {
+            let allocations = AllocationColumns::new(part, "");
+            println!(
+                "{},{},{},{},{},{},{},{},{},{},{}",
+                report.day,
+                part.part,
+                part.answer.is_ok(),
+                part.status(),
+                csv_field(
+                    &part
+                        .answer
+                        .as_ref()
+                        .map(ToString::to_string)
+                        .unwrap_or_default()
+                ),
+                report.parse_elapsed.as_nanos(),
+                part.elapsed.as_nanos(),
+                allocations.allocations,
+                allocations.bytes,
+                allocations.peak,
+                csv_field(&part.error_chain().join(": ")),
+            );
+        }
+    }
+}
+
+/// The allocation stats of a part formatted for JSON or CSV, or `missing` if they weren't counted.
+struct AllocationColumns {
+    allocations: String,
+    bytes: String,
+    peak: String,
+}
+
+impl AllocationColumns {
+    fn new(part: &PartReport, missing: &str) -> Self {
+        match part.allocations {
+            Some(stats) => Self {
+                allocations: stats.allocations.to_string(),
+                bytes: stats.bytes.to_string(),
+                peak: stats.peak.to_string(),
+            },
+            None => Self {
+                allocations: missing.to_owned(),
+                bytes: missing.to_owned(),
+                peak: missing.to_owned(),
+            },
+        }
+    }
+}
+
+/// An event as a JSON object with its name under `event` and its fields as strings.
+fn json_event(event: &Event) -> String {
+    let fields = [("event", event.name)]
+        .into_iter()
+        .chain(
+            event
+                .fields
+                .iter()
+                .map(|(name, value)| (*name, value.as_str())),
+        )
+        .map(|(name, value)| format!("{}:{}", json_string(name), json_string(value)))
+        .collect::<Vec<_>>();
+
+    format!("{{{}}}", fields.join(","))
+}
+
+/// Print the trace events of every part that has any, grouped by day and part.
+pub fn print_traces(reports: &[DayReport]) {
+    for report in reports {
+        for part in &report.parts {
+            if part.trace.is_empty() {
+                continue;
+            }
+
+            println!(
+                "====== Trace of day {} part {} ======",
+                report.day, part.part
+            );
+            for event in &part.trace {
+                println!("{event}");
+            }
+        }
+    }
+}
+
+fn json_string(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len() + 2);
+    escaped.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c if c.is_control() => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    escaped.push('"');
+
+    escaped
+}
+
+fn csv_field(s: &str) -> String {
+    if s.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_owned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn json_string_escapes() {
+        assert_eq!(json_string("54338"), r#""54338""#);
+        assert_eq!(json_string(r#"say "hi""#), r#""say \"hi\"""#);
+        assert_eq!(json_string(r"C:\input"), r#""C:\\input""#);
+        assert_eq!(json_string("a\nb\r\tc"), r#""a\nb\r\tc""#);
+        assert_eq!(json_string("\u{0}\u{1b}"), r#""\u0000\u001b""#);
+        assert_eq!(json_string("über"), r#""über""#);
+    }
+
+    #[test]
+    fn csv_field_quotes_when_needed() {
+        assert_eq!(csv_field("54338"), "54338");
+        assert_eq!(csv_field("a,b"), r#""a,b""#);
+        assert_eq!(csv_field(r#"say "hi""#), r#""say ""hi""""#);
+        assert_eq!(csv_field("line\nbreak"), "\"line\nbreak\"");
+        assert_eq!(csv_field("carriage\rreturn"), "\"carriage\rreturn\"");
+    }
+}
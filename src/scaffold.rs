@@ -0,0 +1,197 @@
+use std::{fs, path::Path};
+
+use advent_of_code_2023::{input, DAYS};
+use anyhow::{bail, Context, Result};
+
+const LIB_PATH: &str = "src/lib.rs";
+const REGISTRY_START: &str = "days! {\n";
+
+const TEMPLATE: &str = r#"use std::io::BufRead;
+
+use anyhow::{bail, Result};
+
+use crate::{Answer, Input, Solutions};
+
+#[derive(Default)]
+pub struct Day{day};
+
+impl Solutions for Day{day} {
+    type Parsed = Vec<String>;
+
+    fn parse(&mut self, input: Input<'_>) -> Result<Self::Parsed> {
+        Ok(input.lines().collect::<Result<_, _>>()?)
+    }
+
+    fn part1(&mut self, _parsed: &Self::Parsed) -> Result<Answer> {
+        bail!("Not implemented yet")
+    }
+
+    fn part2(&mut self, _parsed: &Self::Parsed) -> Result<Answer> {
+        bail!("Not implemented yet")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "";
+
+    #[test]
+    #[ignore = "fill in the example from the puzzle"]
+    fn part1_example() {
+        let parsed = Day{day}.parse(EXAMPLE.into()).unwrap();
+        assert_eq!(Day{day}.part1(&parsed).unwrap(), Answer::from(0));
+    }
+
+    #[test]
+    #[ignore = "fill in the example from the puzzle"]
+    fn part2_example() {
+        let parsed = Day{day}.parse(EXAMPLE.into()).unwrap();
+        assert_eq!(Day{day}.part2(&parsed).unwrap(), Answer::from(0));
+    }
+}
+"#;
+
+/// Generate `src/day#.rs` from the template, register it in `src/lib.rs` and create an empty
+/// input file for it. Has to be run from the root of the repository.
+pub fn new_day(day: usize) -> Result<()> {
+    if !(1..=25).contains(&day) {
+        bail!("Day {day} is not a day of Advent of Code");
+    }
+    if DAYS.iter().any(|registration| registration.day == day) {
+        bail!("Day {day} is already registered");
+    }
+
+    let module_path = format!("src/day{day}.rs");
+    let module_path = Path::new(&module_path);
+    if module_path.exists() {
+        bail!("{} already exists", module_path.display());
+    }
+    let lib = fs::read_to_string(LIB_PATH).with_context(|| {
+        format!("Unable to read {LIB_PATH}. Please run this from the root of the repository.")
+    })?;
+    let lib = register(&lib, day)?;
+
+    fs::write(module_path, TEMPLATE.replace("{day}", &day.to_string()))
+        .with_context(|| format!("Unable to write {}", module_path.display()))?;
+    println!("Created {}", module_path.display());
+    fs::write(LIB_PATH, lib).with_context(|| format!("Unable to write {LIB_PATH}"))?;
+    println!("Registered day {day} in {LIB_PATH}");
+
+    let input_path = input::default_path(day);
+    if input_path.exists() {
+        println!("Kept existing {}", input_path.display());
+    } else {
+        if let Some(parent) = input_path.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("Unable to create {}", parent.display()))?;
+        }
+        fs::write(&input_path, "")
+            .with_context(|| format!("Unable to write {}", input_path.display()))?;
+        println!("Created {}", input_path.display());
+    }
+
+    Ok(())
+}
+
+/// Declare the module of `day` and insert a line for it into the `days!` invocation in the source
+/// of `lib.rs`, keeping the days in order.
+fn register(lib: &str, day: usize) -> Result<String> {
+    let lib = declare_module(lib, day)?;
+    let start = lib
+        .find(REGISTRY_START)
+        .context("Unable to find the days! invocation")?
+        + REGISTRY_START.len();
+    let end = start
+        + lib[start..]
+            .find('}')
+            .context("Unable to find the end of the days! invocation")?;
+
+    let mut lines = lib[start..end].lines().collect::<Vec<_>>();
+    let entry = format!("    {day} => day{day}::Day{day},");
+    let position = lines
+        .iter()
+        .position(|line| {
+            line.split("=>")
+                .next()
+                .and_then(|registered| registered.trim().parse::<usize>().ok())
+                .is_some_and(|registered| registered > day)
+        })
+        .unwrap_or(lines.len());
+    lines.insert(position, &entry);
+
+    Ok(format!(
+        "{}{}\n{}",
+        &lib[..start],
+        lines.join("\n"),
+        &lib[end..]
+    ))
+}
+
+/// Insert `pub mod day#;` for `day` among the declarations of the other day modules in the source
+/// of `lib.rs`, keeping them in order.
+fn declare_module(lib: &str, day: usize) -> Result<String> {
+    let declared_day = |line: &str| {
+        line.strip_prefix("pub mod day")
+            .and_then(|rest| rest.strip_suffix(';'))
+            .and_then(|declared| declared.parse::<usize>().ok())
+    };
+
+    let mut lines = lib.lines().collect::<Vec<_>>();
+    let last = lines
+        .iter()
+        .rposition(|line| declared_day(line).is_some())
+        .context("Unable to find the declarations of the day modules")?;
+    let declaration = format!("pub mod day{day};");
+    let position = lines
+        .iter()
+        .position(|line| declared_day(line).is_some_and(|declared| declared > day))
+        .unwrap_or(last + 1);
+    lines.insert(position, &declaration);
+
+    Ok(format!("{}\n", lines.join("\n")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LIB: &str = "\
+pub mod answer;
+
+pub mod day1;
+pub mod day3;
+
+days! {
+    1 => day1::Day1,
+    3 => day3::Day3,
+}
+
+const _: () = ();
+";
+
+    #[test]
+    fn register_in_order() {
+        assert_eq!(
+            register(LIB, 2).unwrap(),
+            LIB.replace("pub mod day3;", "pub mod day2;\npub mod day3;")
+                .replace("    3 =>", "    2 => day2::Day2,\n    3 =>")
+        );
+    }
+
+    #[test]
+    fn register_last() {
+        assert_eq!(
+            register(LIB, 4).unwrap(),
+            LIB.replace("day3;\n", "day3;\npub mod day4;\n")
+                .replace("Day3,\n", "Day3,\n    4 => day4::Day4,\n")
+        );
+    }
+
+    #[test]
+    fn register_without_invocation() {
+        assert!(register("pub mod answer;\n", 2).is_err());
+        assert!(register("pub mod day1;\n", 2).is_err());
+    }
+}
@@ -0,0 +1,81 @@
+use std::{
+    fmt::{self, Display},
+    sync::{Arc, Mutex},
+};
+
+/// A structured intermediate value emitted while solving, such as the calibration value of a line.
+#[derive(Clone, Debug)]
+pub struct Event {
+    pub name: &'static str,
+    pub fields: Vec<(&'static str, String)>,
+}
+
+impl Event {
+    pub fn new(name: &'static str) -> Self {
+        Self {
+            name,
+            fields: Vec::new(),
+        }
+    }
+
+    pub fn field(mut self, name: &'static str, value: impl Display) -> Self {
+        self.fields.push((name, value.to_string()));
+        self
+    }
+}
+
+impl Display for Event {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name)?;
+        for (name, value) in &self.fields {
+            write!(f, " {name}={value}")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Where solutions emit trace events into, handed to them with [`crate::Solutions::set_trace`].
+///
+/// Tracing is disabled by default, in which case [`Trace::emit`] doesn't even build the event, so
+/// emitting costs no more than a branch:
+///
+/// ```
+/// # use advent_of_code_2023::trace::{Event, Trace};
+/// let trace = Trace::enabled();
+/// trace.emit(|| Event::new("calibration value").field("line", 1).field("value", 12));
+/// assert_eq!(trace.take()[0].to_string(), "calibration value line=1 value=12");
+/// ```
+#[derive(Clone, Default)]
+pub struct Trace(Option<Arc<Mutex<Vec<Event>>>>);
+
+impl Trace {
+    /// A trace that collects the events emitted into it.
+    pub fn enabled() -> Self {
+        Self(Some(Arc::default()))
+    }
+
+    /// Emit the event built by `event`, which is only called if tracing is enabled.
+    #[inline]
+    pub fn emit(&self, event: impl FnOnce() -> Event) {
+        if let Some(events) = &self.0 {
+            let event = event();
+            events
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner())
+                .push(event);
+        }
+    }
+
+    /// Take the events emitted so far.
+    pub fn take(&self) -> Vec<Event> {
+        match &self.0 {
+            Some(events) => std::mem::take(
+                &mut *events
+                    .lock()
+                    .unwrap_or_else(|poisoned| poisoned.into_inner()),
+            ),
+            None => Vec::new(),
+        }
+    }
+}
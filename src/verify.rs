@@ -0,0 +1,91 @@
+use crate::{answers::Answers, report::DayReport};
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Verdict {
+    Pass,
+    Fail,
+    Missing,
+}
+
+pub struct Verification {
+    pub day: usize,
+    pub part: u8,
+    pub expected: Option<String>,
+    pub actual: String,
+    pub verdict: Verdict,
+}
+
+pub fn verify(reports: &[DayReport], answers: &Answers) -> Vec<Verification> {
+    reports
+        .iter()
+        .flat_map(|report| report.parts.iter().map(move |part| (report.day, part)))
+        .map(|(day, part)| {
+            let verdict = match &part.answer {
+                _ if !answers.contains(day, part.part) => Verdict::Missing,
+                Ok(actual) if answers.matches(day, part.part, actual) => Verdict::Pass,
+                _ => Verdict::Fail,
+            };
+            let actual = match &part.answer {
+                Ok(answer) => answer.to_string(),
+                Err(error) => format!("error: {error:#}"),
+            };
+
+            Verification {
+                day,
+                part: part.part,
+                expected: answers.describe(day, part.part).map(str::to_owned),
+                actual,
+                verdict,
+            }
+        })
+        .collect()
+}
+
+/// Record the answer of every part that succeeded in `answers`, printing for each part whether
+/// it was recorded. Returns the number of parts that failed and weren't recorded.
+pub fn record(reports: &[DayReport], answers: &mut Answers) -> usize {
+    let mut failures = 0;
+    for report in reports {
+        for part in &report.parts {
+            match &part.answer {
+                Ok(answer) => {
+                    answers.record(report.day, part.part, answer);
+                    println!("Day {} part {}: recorded", report.day, part.part);
+                }
+                Err(error) => {
+                    failures += 1;
+                    println!("Day {} part {}: error: {:#}", report.day, part.part, error);
+                }
+            }
+        }
+    }
+
+    failures
+}
+
+pub fn print_verifications(verifications: &[Verification]) {
+    let expected_width = verifications
+        .iter()
+        .filter_map(|verification| verification.expected.as_ref())
+        .map(String::len)
+        .max()
+        .unwrap_or(0)
+        .max("Expected".len());
+
+    println!("Day  Part  Status   {:expected_width$}  Actual", "Expected");
+    for verification in verifications {
+        let status = match verification.verdict {
+            Verdict::Pass => "PASS",
+            Verdict::Fail => "FAIL",
+            Verdict::Missing => "MISSING",
+        };
+        println!(
+            "{:>3}  {:>4}  {:7}  {:expected_width$}  {}",
+            verification.day,
+            verification.part,
+            status,
+            verification.expected.as_deref().unwrap_or("-"),
+            verification.actual,
+        );
+    }
+}
@@ -0,0 +1,169 @@
+use std::{
+    collections::HashMap,
+    env, fs,
+    path::{Path, PathBuf},
+    process::Command,
+    thread,
+    time::{Duration, SystemTime},
+};
+
+use advent_of_code_2023::{
+    input::InputSource,
+    report::{print_parse_error, DayReport},
+    Day,
+};
+use anyhow::{bail, Context, Result};
+
+/// Answers of the previous run, handed down to the new process when re-executing after a source
+/// change, as `part<TAB>answer` lines.
+const PREVIOUS_ANSWERS_VAR: &str = "AOC_WATCH_PREVIOUS_ANSWERS";
+
+/// Run `day`, then keep polling its input and source files. The day is re-run in-process when
+/// the input changes, and the whole program is rebuilt and re-executed with `cargo run` when the
+/// source changes. Each run shows how the answers differ from the run before.
+pub fn watch(day: Day, parts: &[u8], timeout: Option<Duration>, interval: Duration) -> Result<()> {
+    let source_path = PathBuf::from(format!("src/day{}.rs", day.day));
+    let input_path = match &day.input {
+        InputSource::File(path) => path.clone(),
+        InputSource::Memory(_) => bail!("Watch mode needs an input file, it can't watch stdin"),
+    };
+
+    let mut previous = previous_answers_from_env();
+    let mut input_modified = modified(&input_path);
+    let mut source_modified = modified(&source_path);
+
+    previous = run_and_diff(&day, parts, timeout, &previous);
+    println!(
+        "Watching {} and {} for changes...",
+        input_path.display(),
+        source_path.display()
+    );
+
+    loop {
+        thread::sleep(interval);
+
+        let source_now = modified(&source_path);
+        if source_now != source_modified {
+            source_modified = source_now;
+            println!("{} changed, rebuilding...", source_path.display());
+            if rebuild() {
+                return reexec(&previous);
+            }
+            println!("Build failed, still watching...");
+        }
+
+        let input_now = modified(&input_path);
+        if input_now != input_modified {
+            input_modified = input_now;
+            previous = run_and_diff(&day, parts, timeout, &previous);
+        }
+    }
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
+
+/// Run the day and print each part's result next to the answer of the previous run.
+fn run_and_diff(
+    day: &Day,
+    parts: &[u8],
+    timeout: Option<Duration>,
+    previous: &HashMap<u8, String>,
+) -> HashMap<u8, String> {
+    let DayReport {
+        day: day_number,
+        parts: reports,
+        ..
+    } = day.run_isolated(parts, timeout);
+
+    println!("====== Day {day_number} ======");
+    let mut answers = HashMap::new();
+    for report in reports {
+        match report.answer {
+            Ok(answer) => {
+                let answer = answer.to_string();
+                let diff = match previous.get(&report.part) {
+                    None => String::new(),
+                    Some(previous) if *previous == answer => " (unchanged)".to_owned(),
+                    Some(previous) => format!(" (changed, was {previous})"),
+                };
+                println!("Part {}: {}{}", report.part, answer, diff);
+                answers.insert(report.part, answer);
+            }
+            Err(error) => {
+                println!("Part {}: error: {:#}", report.part, error);
+                print_parse_error(&error, "");
+                // Keep the last good answer around to compare the next successful run against
+                if let Some(previous) = previous.get(&report.part) {
+                    answers.insert(report.part, previous.clone());
+                }
+            }
+        }
+    }
+
+    answers
+}
+
+fn cargo_command(subcommand: &str) -> Command {
+    let mut command = Command::new(env::var_os("CARGO").unwrap_or_else(|| "cargo".into()));
+    command.arg(subcommand);
+    if !cfg!(debug_assertions) {
+        command.arg("--release");
+    }
+
+    command
+}
+
+/// Build first, so a mistake in the source doesn't end the watch.
+fn rebuild() -> bool {
+    cargo_command("build")
+        .status()
+        .is_ok_and(|status| status.success())
+}
+
+/// Replace this process with a fresh `cargo run` with the same arguments.
+fn reexec(previous: &HashMap<u8, String>) -> Result<()> {
+    let mut command = cargo_command("run");
+    command
+        .arg("--quiet")
+        .arg("--")
+        .args(env::args_os().skip(1))
+        .env(
+            PREVIOUS_ANSWERS_VAR,
+            previous
+                .iter()
+                .map(|(part, answer)| format!("{part}\t{answer}"))
+                .collect::<Vec<_>>()
+                .join("\n"),
+        );
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+
+        Err(command.exec()).context("Unable to re-execute cargo run")
+    }
+
+    #[cfg(not(unix))]
+    {
+        let status = command.status().context("Unable to re-execute cargo run")?;
+        std::process::exit(status.code().unwrap_or(1))
+    }
+}
+
+fn previous_answers_from_env() -> HashMap<u8, String> {
+    let Ok(previous) = env::var(PREVIOUS_ANSWERS_VAR) else {
+        return HashMap::new();
+    };
+
+    previous
+        .lines()
+        .filter_map(|line| {
+            let (part, answer) = line.split_once('\t')?;
+            Some((part.parse().ok()?, answer.to_owned()))
+        })
+        .collect()
+}